@@ -8,21 +8,44 @@
 // except according to those terms.
 
 use getch::Getch;
-use impaired::{Comparisons, Item, Scores};
-use itertools::Itertools;
+use impaired::{
+    Aggregated, Aggregation, Comparisons, Item, Outcome, Report, Scorer, Scores, Session,
+};
 use std::{
     env,
     io::{stdout, Write},
 };
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let items: Vec<Item<String>> = env::args().skip(1).map(Item).collect();
+    let (options, items): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let items: Vec<Item<String>> = items.into_iter().map(Item).collect();
+    // Rank by wins unless another aggregation is chosen through `--scorer=<aggregation>`.
+    let mut scorer: Box<dyn Scorer<String>> = Box::new(Scores::new());
+    // Print the final scores unless a report is requested through `--report=markdown|html`.
+    let mut report = None;
+    for option in options {
+        if let Some(name) = option.strip_prefix("--scorer=") {
+            scorer = match name {
+                "wins" => Box::new(Scores::new()),
+                name => Box::new(Aggregated::new(name.parse::<Aggregation>()?)),
+            };
+        } else if let Some(format) = option.strip_prefix("--report=") {
+            match format {
+                "markdown" | "html" => report = Some(format.to_owned()),
+                _ => return Err(format!("unknown report format '{}'", format).into()),
+            }
+        } else {
+            return Err(format!("unknown option '{}'", option).into());
+        }
+    }
     if items.is_empty() {
         let (example_name, _) = file!()
             .split_once('.')
             .expect("Failed to get example file name");
         eprintln!(
-            "USAGE: cargo run --example {} -- item1 item2 ...",
+            "USAGE: cargo run --example {} -- [--scorer=wins|<aggregation>] [--report=markdown|html] \
+             item1 item2 ...",
             example_name
         );
         std::process::exit(1);
@@ -30,36 +53,39 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let getch = Getch::new();
 
     let comparisons: Comparisons<_> = Comparisons::new(items.iter());
-    let mut scores: Scores<_> = Scores::new();
+    let mut session = Session::new(&comparisons).scorer(scorer);
 
-    for (comparison, result_tracker) in comparisons.retain_item_iterator() {
+    while let Some(comparison) = session.current_question() {
         println!("A: '{}'  vs.", comparison.left);
         println!("B: '{}'", comparison.right);
         print!("=> Choose by typing 'a' or 'b': ");
         stdout().flush()?;
         loop {
             let char = getch.getch()?;
-            match char.to_ascii_lowercase() as char {
-                'a' => {
-                    scores.track(comparison.left, comparison.right);
-                    result_tracker.winner(comparison.left);
-                }
-                'b' => {
-                    scores.track(comparison.right, comparison.left);
-                    result_tracker.winner(comparison.right);
-                }
+            let outcome = match char.to_ascii_lowercase() as char {
+                'a' => Outcome::Left,
+                'b' => Outcome::Right,
                 _ => {
                     continue;
                 }
-            }
+            };
+            session.answer(outcome)?;
             println!("\n");
             break;
         }
     }
 
-    println!("\nFinal scores:");
-    for (item, score) in scores.iter().sorted_by(|(_, a), (_, b)| b.cmp(a)) {
-        println!("- {}: {} votes", item, score);
+    match report.as_deref() {
+        Some("markdown") => Report::new(&session)
+            .decisions(true)
+            .write_markdown(stdout())?,
+        Some(_) => Report::new(&session).decisions(true).write_html(stdout())?,
+        None => {
+            println!("\nFinal scores:");
+            for ranked in &session.ranking() {
+                println!("{}. {}: {}", ranked.rank, ranked.item, ranked.score);
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,227 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt::{Display, Formatter},
+    io::Read,
+    path::Path,
+    str::FromStr,
+};
+
+/// The format the items are read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One item per line.
+    Lines,
+    /// One item per record, taken from a single column of a CSV file with a header row.
+    Csv,
+    /// A JSON array of strings.
+    Json,
+}
+
+impl Format {
+    /// Guess the format from the extension of the given path, defaulting to one item per line.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("csv") => Format::Csv,
+            Some("json") => Format::Json,
+            _ => Format::Lines,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lines" => Ok(Format::Lines),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(InputError::UnknownFormat(s.to_owned())),
+        }
+    }
+}
+
+/// The column of a CSV file to take the items from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    /// The column with the given name in the header row.
+    Name(String),
+    /// The column at the given index, starting at `0`.
+    Index(usize),
+}
+
+impl Default for Column {
+    fn default() -> Self {
+        Column::Index(0)
+    }
+}
+
+impl FromStr for Column {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.parse()
+            .map(Column::Index)
+            .unwrap_or_else(|_| Column::Name(s.to_owned())))
+    }
+}
+
+/// An error that occurred while reading the items.
+#[derive(Debug)]
+pub enum InputError {
+    /// The format is not one of `lines`, `csv` or `json`.
+    UnknownFormat(String),
+    /// The CSV file does not have the requested column.
+    MissingColumn(Column),
+    /// The input could not be read.
+    Io(std::io::Error),
+    /// The CSV input is malformed.
+    Csv(csv::Error),
+    /// The JSON input is malformed or not an array of strings.
+    Json(serde_json::Error),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::UnknownFormat(format) => write!(
+                f,
+                "unknown input format '{}', expected 'lines', 'csv' or 'json'",
+                format
+            ),
+            InputError::MissingColumn(Column::Name(name)) => {
+                write!(f, "the CSV input has no column named '{}'", name)
+            }
+            InputError::MissingColumn(Column::Index(index)) => {
+                write!(f, "the CSV input has no column {}", index)
+            }
+            InputError::Io(error) => write!(f, "failed to read the items: {}", error),
+            InputError::Csv(error) => write!(f, "failed to parse the CSV input: {}", error),
+            InputError::Json(error) => write!(f, "failed to parse the JSON input: {}", error),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::UnknownFormat(_) | InputError::MissingColumn(_) => None,
+            InputError::Io(error) => Some(error),
+            InputError::Csv(error) => Some(error),
+            InputError::Json(error) => Some(error),
+        }
+    }
+}
+
+/// Read the items from the given reader.
+///
+/// Items are trimmed, and empty items are skipped. In the line and CSV formats, lines starting with
+/// `#` are comments and skipped as well. Every item is only kept once, in the order it first
+/// appeared in.
+pub fn read_items(
+    mut reader: impl Read,
+    format: Format,
+    column: &Column,
+) -> Result<Vec<String>, InputError> {
+    let items: Vec<String> = match format {
+        Format::Lines => {
+            let mut input = String::new();
+            reader.read_to_string(&mut input).map_err(InputError::Io)?;
+            input
+                .lines()
+                .filter(|line| !line.trim_start().starts_with('#'))
+                .map(str::to_owned)
+                .collect()
+        }
+        Format::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .comment(Some(b'#'))
+                .flexible(true)
+                .from_reader(reader);
+            let index = match column {
+                Column::Index(index) => *index,
+                Column::Name(name) => reader
+                    .headers()
+                    .map_err(InputError::Csv)?
+                    .iter()
+                    .position(|header| header.trim() == name)
+                    .ok_or_else(|| InputError::MissingColumn(column.clone()))?,
+            };
+            if index >= reader.headers().map_err(InputError::Csv)?.len() {
+                return Err(InputError::MissingColumn(column.clone()));
+            }
+            let mut items = Vec::new();
+            for record in reader.records() {
+                let record = record.map_err(InputError::Csv)?;
+                items.extend(record.get(index).map(str::to_owned));
+            }
+            items
+        }
+        Format::Json => serde_json::from_reader(reader).map_err(InputError::Json)?,
+    };
+
+    let mut seen = HashSet::new();
+    Ok(items
+        .into_iter()
+        .map(|item| item.trim().to_owned())
+        .filter(|item| !item.is_empty() && seen.insert(item.clone()))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lines_skip_comments_blanks_and_duplicates() {
+        let input = "# Languages\nRust\n\n  C++  \nRust\n   # indented comment\nJava\n";
+        let items = read_items(input.as_bytes(), Format::Lines, &Column::default()).unwrap();
+        assert_eq!(items, vec!["Rust", "C++", "Java"]);
+    }
+
+    #[test]
+    fn csv_reads_a_single_column() {
+        let input = "id,name\n# comment\n1,Rust\n2,\"C++, the language\"\n\n3,Rust\n";
+        let items = read_items(input.as_bytes(), Format::Csv, &"name".parse().unwrap()).unwrap();
+        assert_eq!(items, vec!["Rust", "C++, the language"]);
+
+        let items = read_items(input.as_bytes(), Format::Csv, &Column::Index(0)).unwrap();
+        assert_eq!(items, vec!["1", "2", "3"]);
+
+        assert!(matches!(
+            read_items(input.as_bytes(), Format::Csv, &"title".parse().unwrap()),
+            Err(InputError::MissingColumn(_))
+        ));
+    }
+
+    #[test]
+    fn json_reads_an_array_of_strings() {
+        let input = r#"["Rust", "Multi-line\ndescription", "Rust", ""]"#;
+        let items = read_items(input.as_bytes(), Format::Json, &Column::default()).unwrap();
+        assert_eq!(items, vec!["Rust", "Multi-line\ndescription"]);
+
+        assert!(matches!(
+            read_items(
+                r#"{"items": []}"#.as_bytes(),
+                Format::Json,
+                &Column::default()
+            ),
+            Err(InputError::Json(_))
+        ));
+    }
+}
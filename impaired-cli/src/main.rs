@@ -0,0 +1,262 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+mod input;
+mod save;
+mod tui;
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal,
+};
+use impaired::{
+    Aggregated, Aggregation, Comparisons, Item, Outcome, Report, RetainWinner, Scorer, Scores,
+    Session,
+};
+use input::{Column, Format};
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{self, stderr, stdin, stdout, Write},
+    path::PathBuf,
+    process,
+};
+
+const USAGE: &str = "\
+USAGE: impaired [OPTIONS] [FILE]
+       impaired [OPTIONS] --resume <SAVED>
+
+Rank the items read from FILE, or from stdin if FILE is '-' or missing, by comparing them pairwise.
+
+OPTIONS:
+    --format lines|csv|json    The format of the items, guessed from the file extension by default:
+                               one item per line, a column of a CSV file with a header row, or a
+                               JSON array of strings. Blank lines and lines starting with '#' are
+                               skipped, duplicate items are only asked about once.
+    --column <name|index>      The CSV column to read the items from, the first one by default.
+    --scorer wins|<aggregation>
+                               How to rank the items, by their number of wins by default.
+    --report markdown|html     Print a report instead of the final scores.
+    --save <file>              Save the session to <file> after every answer.
+    --resume <file>            Continue the session saved in <file>, and keep saving to it.
+    --tui                      Ask the questions in a full-screen terminal UI, showing both items
+                               side by side along with the progress and the current ranking.
+    -h, --help                 Print this help.
+
+Options can be given as '--option value' or '--option=value'. Press 'q', Esc or Ctrl-C to stop
+answering early: the ranking so far is printed, and a saved session can be resumed later. The
+terminal UI additionally allows ties ('t'), skipping questions ('s') and undoing answers ('u').";
+
+type BoxedScorer<'a> = Box<dyn Scorer<'a, String> + 'a>;
+type CliSession<'a> = Session<'a, String, RetainWinner, BoxedScorer<'a>>;
+
+struct Options {
+    path: Option<PathBuf>,
+    format: Option<Format>,
+    column: Column,
+    scorer: String,
+    report: Option<String>,
+    save: Option<PathBuf>,
+    resume: Option<PathBuf>,
+    tui: bool,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let mut options = Options {
+            path: None,
+            format: None,
+            column: Column::default(),
+            scorer: "wins".to_owned(),
+            report: None,
+            save: None,
+            resume: None,
+            tui: false,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => {
+                    (name.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("the option '{}' requires a value", name))
+            };
+            match name.as_str() {
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                "--format" => options.format = Some(value()?.parse()?),
+                "--column" => options.column = value()?.parse()?,
+                "--scorer" => {
+                    let name = value()?;
+                    // Fail early on unknown scorers, before any question is asked.
+                    scorer(&name)?;
+                    options.scorer = name;
+                }
+                "--report" => match value()?.as_str() {
+                    format @ ("markdown" | "html") => options.report = Some(format.to_owned()),
+                    format => return Err(format!("unknown report format '{}'", format).into()),
+                },
+                "--save" => options.save = Some(PathBuf::from(value()?)),
+                "--resume" => options.resume = Some(PathBuf::from(value()?)),
+                "--tui" => options.tui = true,
+                _ if name.starts_with("--") => {
+                    return Err(format!("unknown option '{}'", name).into())
+                }
+                _ if options.path.is_some() => {
+                    return Err("only a single file of items can be given".into())
+                }
+                "-" => {}
+                _ => options.path = Some(PathBuf::from(arg)),
+            }
+        }
+        if options.resume.is_some() && options.path.is_some() {
+            return Err("the items of a resumed session are taken from the saved session".into());
+        }
+        Ok(options)
+    }
+}
+
+/// Create the scorer with the given name, `wins` or the name of an aggregation method.
+fn scorer<'a>(name: &str) -> Result<BoxedScorer<'a>, Box<dyn Error>> {
+    Ok(match name {
+        "wins" => Box::new(Scores::new()),
+        name => Box::new(Aggregated::new(name.parse::<Aggregation>()?)),
+    })
+}
+
+/// Wait for the answer to the current question, or `None` if the session should be ended early.
+fn read_outcome() -> io::Result<Option<Outcome>> {
+    terminal::enable_raw_mode()?;
+    let outcome = loop {
+        let code = match event::read() {
+            Ok(Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            })) => match code {
+                // Raw mode swallows the interrupt signal, so Ctrl-C has to be handled here.
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => break Ok(None),
+                code => code,
+            },
+            Ok(_) => continue,
+            Err(error) => break Err(error),
+        };
+        match code {
+            KeyCode::Char('a' | 'A') | KeyCode::Left => break Ok(Some(Outcome::Left)),
+            KeyCode::Char('b' | 'B') | KeyCode::Right => break Ok(Some(Outcome::Right)),
+            KeyCode::Char('q' | 'Q') | KeyCode::Esc => break Ok(None),
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+    outcome
+}
+
+/// Ask the questions line by line, until the session is finished or stopped.
+fn ask(session: &mut CliSession, save_path: Option<&PathBuf>) -> Result<(), Box<dyn Error>> {
+    // Questions go to stderr, such that the final output can be redirected on its own.
+    let mut prompt = stderr();
+    while let Some(comparison) = session.current_question() {
+        writeln!(prompt, "A: '{}'  vs.", comparison.left)?;
+        writeln!(prompt, "B: '{}'", comparison.right)?;
+        write!(prompt, "=> Choose by typing 'a' or 'b', or 'q' to stop: ")?;
+        prompt.flush()?;
+        let outcome = read_outcome()?;
+        writeln!(prompt, "\n")?;
+        match outcome {
+            Some(outcome) => {
+                session.answer(outcome)?;
+                if let Some(path) = save_path {
+                    save::save(path, session)?;
+                }
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    let saved = options.resume.as_deref().map(save::load).transpose()?;
+    let items = match (&saved, &options.path) {
+        (Some(saved), _) => saved.items().cloned().collect(),
+        (None, Some(path)) => input::read_items(
+            File::open(path)
+                .map_err(|error| format!("failed to open '{}': {}", path.display(), error))?,
+            options.format.unwrap_or_else(|| Format::from_path(path)),
+            &options.column,
+        )?,
+        (None, None) => input::read_items(
+            stdin().lock(),
+            options.format.unwrap_or(Format::Lines),
+            &options.column,
+        )?,
+    };
+    if items.len() < 2 {
+        return Err("at least two distinct items are required".into());
+    }
+    let items: Vec<Item<String>> = items.into_iter().map(Item).collect();
+
+    let comparisons = Comparisons::new(items.iter());
+    let mut session = Session::new(&comparisons).scorer(scorer(&options.scorer)?);
+    if let Some(saved) = &saved {
+        save::replay(&mut session, saved)?;
+    }
+    // Resumed sessions keep being saved to where they were loaded from.
+    let save_path = options.save.as_ref().or(options.resume.as_ref());
+    if let Some(path) = save_path {
+        save::save(path, &session)?;
+    }
+
+    if options.tui {
+        tui::run(&mut session, save_path.map(PathBuf::as_path))?;
+    } else {
+        ask(&mut session, save_path)?;
+    }
+    if !session.is_finished() {
+        if let Some(path) = save_path {
+            eprintln!(
+                "The session is saved, continue it through 'impaired --resume {}'.\n",
+                path.display()
+            );
+        }
+    }
+
+    match options.report.as_deref() {
+        Some("markdown") => Report::new(&session)
+            .decisions(true)
+            .write_markdown(stdout())?,
+        Some(_) => Report::new(&session).decisions(true).write_html(stdout())?,
+        None => {
+            println!("Final scores:");
+            for ranked in &session.ranking() {
+                println!("{}. {}: {}", ranked.rank, ranked.item, ranked.score);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = Options::parse(env::args().skip(1)).and_then(run);
+    if let Err(error) = result {
+        eprintln!("impaired: {}", error);
+        process::exit(1);
+    }
+}
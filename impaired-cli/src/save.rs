@@ -0,0 +1,136 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use impaired::{Event, EventLog, Outcome, Scorer, Session, Strategy};
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::Path,
+};
+
+/// Save the session to the given file as an [`EventLog`](EventLog).
+///
+/// The log is written to a temporary file next to the given one first, which then replaces it, such
+/// that an interruption while saving never leaves a partially written file behind.
+pub fn save<'a, S, R>(path: &Path, session: &Session<'a, String, S, R>) -> io::Result<()> {
+    let mut temporary_name = path.file_name().unwrap_or_default().to_os_string();
+    temporary_name.push(".tmp");
+    let temporary = path.with_file_name(temporary_name);
+
+    let mut file = File::create(&temporary)?;
+    session.event_log().write_to(BufWriter::new(&mut file))?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+/// Load a session saved through [`save`](save).
+pub fn load(path: &Path) -> Result<EventLog<String>, Box<dyn Error>> {
+    let file = File::open(path)
+        .map_err(|error| format!("failed to open '{}': {}", path.display(), error))?;
+    Ok(EventLog::read_from(BufReader::new(file))?)
+}
+
+/// Answer the questions of the session according to the decisions of the event log.
+///
+/// The session has to be over the items of the log, such that it asks the same questions in the
+/// same order as the saved session did.
+pub fn replay<'a, S: Strategy<'a, String>, R: Scorer<'a, String>>(
+    session: &mut Session<'a, String, S, R>,
+    event_log: &EventLog<String>,
+) -> Result<(), Box<dyn Error>> {
+    let decisions = event_log
+        .events()
+        .iter()
+        .filter(|event| !matches!(event, Event::Item(_)));
+    for (index, event) in decisions.enumerate() {
+        let comparison = session
+            .current_question()
+            .ok_or("the saved session contains more decisions than there are questions")?;
+        let question = (&comparison.left.0, &comparison.right.0);
+        // Draws and skipped comparisons do not depend on the order of the items.
+        let unordered = |left, right| (left, right) == question || (right, left) == question;
+        match event {
+            Event::Result { winner, loser } if (winner, loser) == question => {
+                session.answer(Outcome::Left)?;
+            }
+            Event::Result { winner, loser } if (loser, winner) == question => {
+                session.answer(Outcome::Right)?;
+            }
+            Event::Draw { left, right } if unordered(left, right) => {
+                session.answer(Outcome::Draw)?;
+            }
+            Event::Skip { left, right } if unordered(left, right) => {
+                session.skip()?;
+            }
+            _ => {
+                return Err(format!(
+                    "decision {} of the saved session does not answer the question '{}' vs. '{}'",
+                    index + 1,
+                    question.0,
+                    question.1
+                )
+                .into())
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use impaired::{Comparisons, Item};
+
+    #[test]
+    fn saved_sessions_resume_where_they_stopped() {
+        let items: Vec<Item<String>> = ["Rust", "C++", "Java", "Go"]
+            .iter()
+            .map(|item| Item(item.to_string()))
+            .collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::new(&comparisons);
+        for outcome in [Outcome::Left, Outcome::Draw, Outcome::Right] {
+            session.answer(outcome).unwrap();
+        }
+        session.skip().unwrap();
+
+        let directory = std::env::temp_dir().join(format!("impaired-save-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("session.tsv");
+        save(&path, &session).unwrap();
+        let event_log = load(&path).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let items: Vec<Item<String>> = event_log.items().cloned().map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut resumed = Session::new(&comparisons);
+        replay(&mut resumed, &event_log).unwrap();
+        assert_eq!(resumed.event_log(), session.event_log());
+        assert_eq!(resumed.current_question(), session.current_question());
+    }
+
+    #[test]
+    fn mismatching_decisions_are_rejected() {
+        let items: Vec<Item<String>> = ["a", "b", "c"]
+            .iter()
+            .map(|item| Item(item.to_string()))
+            .collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::new(&comparisons);
+
+        let mut event_log = EventLog::new();
+        event_log.push(Event::Result {
+            winner: "a".to_owned(),
+            loser: "unknown".to_owned(),
+        });
+        assert!(replay(&mut session, &event_log).is_err());
+        assert!(session.history().is_empty());
+    }
+}
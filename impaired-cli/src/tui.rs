@@ -0,0 +1,256 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{save, CliSession};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use impaired::{svg::format_score, Outcome};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout},
+    text::Text,
+    widgets::{Block, Gauge, List, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use std::{
+    error::Error,
+    io::{self, stderr},
+    path::Path,
+};
+
+const HELP: &str = "←/a: left  →/b: right  t: tie  s: skip  u: undo  q: quit";
+
+/// What a key press asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Answer(Outcome),
+    Skip,
+    Undo,
+    Quit,
+}
+
+fn action(key: KeyEvent) -> Option<Action> {
+    Some(match key.code {
+        // Raw mode swallows the interrupt signal, so Ctrl-C has to be handled here.
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+        KeyCode::Left | KeyCode::Char('a' | 'h') => Action::Answer(Outcome::Left),
+        KeyCode::Right | KeyCode::Char('b' | 'l') => Action::Answer(Outcome::Right),
+        KeyCode::Down | KeyCode::Char('t' | '=') => Action::Answer(Outcome::Draw),
+        KeyCode::Char('s' | ' ') => Action::Skip,
+        KeyCode::Backspace | KeyCode::Char('u') => Action::Undo,
+        KeyCode::Esc | KeyCode::Char('q') => Action::Quit,
+        _ => return None,
+    })
+}
+
+/// Switches the terminal into raw mode and the alternate screen, and back once dropped, even when
+/// panicking.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(stderr(), EnterAlternateScreen, Clear(ClearType::All))?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(stderr(), LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Run the session in a full-screen terminal UI until it is quit.
+///
+/// The session is saved to `save_path` after every change, if given. The UI is drawn to stderr, such
+/// that stdout can be redirected for the results.
+pub fn run(session: &mut CliSession, save_path: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let _screen = Screen::enter()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stderr()))?;
+
+    let mut status = String::new();
+    loop {
+        terminal.draw(|frame| draw(frame, session, &status))?;
+        let action = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match action(key) {
+                Some(action) => action,
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        status.clear();
+        let changed = match action {
+            Action::Answer(outcome) => session.answer(outcome).is_ok(),
+            Action::Skip => session.skip().is_ok(),
+            Action::Undo => session.undo().is_some(),
+            Action::Quit => return Ok(()),
+        };
+        if !changed {
+            status.push_str(match action {
+                Action::Undo => "There is nothing to undo.",
+                _ => "All questions are answered, press 'q' to show the results.",
+            });
+        } else if let Some(path) = save_path {
+            save::save(path, session)?;
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, session: &CliSession, status: &str) {
+    let [progress, main, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [question, ranking] =
+        Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)]).areas(main);
+
+    let done = session.history().len() + session.skipped().len();
+    let total = done + session.remaining();
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title(" Progress "))
+            .ratio(if total == 0 {
+                1.0
+            } else {
+                done as f64 / total as f64
+            })
+            .label(format!("{}/{}", done, total)),
+        progress,
+    );
+
+    match session.current_question() {
+        Some(comparison) => {
+            let [left, right] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(question);
+            for (area, title, item) in [
+                (left, " ← A ", comparison.left),
+                (right, " B → ", comparison.right),
+            ] {
+                frame.render_widget(
+                    Paragraph::new(Text::raw(item.0.as_str()))
+                        .block(Block::bordered().title(title))
+                        .wrap(Wrap { trim: false }),
+                    area,
+                );
+            }
+        }
+        None => frame.render_widget(
+            Paragraph::new(
+                "All questions are answered.\n\n\
+                 Press 'u' to undo the last answer, or 'q' to show the results.",
+            )
+            .block(Block::bordered().title(" Done "))
+            .wrap(Wrap { trim: false }),
+            question,
+        ),
+    }
+
+    let entries: Vec<String> = session
+        .ranking()
+        .iter()
+        .map(|ranked| {
+            // Multi-line items are shortened to their first line.
+            let first_line = ranked.item.0.lines().next().unwrap_or_default();
+            format!(
+                "{:>2}. {} ({})",
+                ranked.rank,
+                first_line,
+                format_score(ranked.score)
+            )
+        })
+        .collect();
+    frame.render_widget(
+        List::new(entries).block(Block::bordered().title(" Ranking ")),
+        ranking,
+    );
+
+    frame.render_widget(
+        Paragraph::new(if status.is_empty() { HELP } else { status }),
+        help,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use impaired::{Comparisons, Item, Scores, Session};
+    use ratatui::backend::TestBackend;
+
+    fn screen(session: &CliSession, status: &str) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 16)).unwrap();
+        terminal.draw(|frame| draw(frame, session, status)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut screen = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                screen.push_str(buffer[(x, y)].symbol());
+            }
+            screen.push('\n');
+        }
+        screen
+    }
+
+    #[test]
+    fn keys_map_to_actions() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(
+            action(key(KeyCode::Left)),
+            Some(Action::Answer(Outcome::Left))
+        );
+        assert_eq!(
+            action(key(KeyCode::Char('b'))),
+            Some(Action::Answer(Outcome::Right))
+        );
+        assert_eq!(
+            action(key(KeyCode::Char('t'))),
+            Some(Action::Answer(Outcome::Draw))
+        );
+        assert_eq!(action(key(KeyCode::Char('s'))), Some(Action::Skip));
+        assert_eq!(action(key(KeyCode::Char('u'))), Some(Action::Undo));
+        assert_eq!(
+            action(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        assert_eq!(action(key(KeyCode::Char('x'))), None);
+    }
+
+    #[test]
+    fn items_are_shown_side_by_side_with_the_ranking() {
+        let items = [
+            Item("Rust\nfast and safe".to_owned()),
+            Item("Zig".to_owned()),
+        ];
+        let comparisons = Comparisons::new(items.iter());
+        let mut session: CliSession = Session::new(&comparisons).scorer(Box::new(Scores::new()));
+
+        let screen = screen(&session, "");
+        let line = |text: &str| screen.lines().find(|line| line.contains(text)).unwrap();
+        // Both items share the line of their first line, the second line stays within its panel.
+        assert!(line("Rust").contains("Zig"));
+        assert!(line("fast and safe").contains("│"));
+        assert!(screen.contains("0/1"));
+        assert!(screen.contains(HELP));
+
+        session.answer(Outcome::Right).unwrap();
+        let screen = self::screen(&session, "There is nothing to undo.");
+        assert!(screen.contains("1. Zig (1)"));
+        assert!(screen.contains("1/1"));
+        assert!(screen.contains("All questions are answered."));
+        assert!(screen.contains("There is nothing to undo."));
+    }
+}
@@ -1,10 +1,13 @@
 import init, {
+    getCharts,
     getItems,
+    getScorers,
     getScores,
     hasOngoingComparison,
     nextComparison,
     pushItem,
     resetComparison,
+    setScorer,
     startComparison,
     trackResult,
 } from './pkg/impaired_web.js';
@@ -18,6 +21,8 @@ const comparisonLeft = document.getElementById("comparison-left");
 const comparisonRight = document.getElementById("comparison-right");
 const resultsContainer = document.getElementById("results-container");
 const results = document.getElementById("results");
+const resultsScorer = document.getElementById("results-scorer");
+const resultsCharts = document.getElementById("results-charts");
 let currentComparison = null;
 
 const populateComparisonSetupModal = (reset = false) => {
@@ -142,15 +147,20 @@ const displayResults = () => {
     // Show results container
     resultsContainer.classList.remove("d-none");
 
-    // Create and display a card for each item, sorted by their score, in descending order (i.e. the first item is the
-    // one with the most votes).
+    // Create and display a card for each item. The scores are already ordered from best to worst according to the
+    // selected scorer, tied items share their rank (e.g. "1, 2, 2, 4").
     const scores = getScores();
-    const cards = scores.sort((a, b) => b.score - a.score).map(({item, _score}) => {
+    const cards = scores.map(({item, rank}) => {
         const li = document.createElement("li");
+        li.value = rank;
         li.appendChild(generateCardForItem(item, true));
         return li;
     });
     results.replaceChildren(...cards);
+
+    // The charts are self-contained SVG documents rendered by the library.
+    const charts = getCharts();
+    resultsCharts.innerHTML = charts ? charts.heatmap + charts.scores : "";
 }
 
 const setUpNextComparison = () => {
@@ -168,8 +178,21 @@ const setUpNextComparison = () => {
     comparisonRight.replaceChildren(cardRight);
 }
 
+resultsScorer.addEventListener("change", () => {
+    setScorer(resultsScorer.value);
+    displayResults();
+});
+
 const run = async () => {
     await init();
+
+    const options = getScorers().map((scorer) => {
+        const option = document.createElement("option");
+        option.value = scorer;
+        option.textContent = scorer;
+        return option;
+    });
+    resultsScorer.replaceChildren(...options);
 };
 
 await run();
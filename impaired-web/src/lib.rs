@@ -7,7 +7,10 @@
 // option. This file may not be copied, modified or distributed
 // except according to those terms.
 
-use impaired::{Comparisons, RetainItemIterator, Scores};
+use impaired::{
+    svg, Aggregated, Aggregation, Bootstrap, BradleyTerry, Comparisons, Outcome, RetainWinner,
+    Scorer, Scores, Session,
+};
 use indexmap::IndexSet;
 use ouroboros::self_referencing;
 use serde::Serialize;
@@ -53,7 +56,27 @@ pub struct Comparison {
 #[derive(Serialize, Clone)]
 pub struct Score {
     pub item: Item,
-    pub score: u32,
+    pub score: f64,
+    pub rank: u32,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct Charts {
+    pub heatmap: String,
+    pub scores: String,
+}
+
+type BoxedScorer<'a> = Box<dyn Scorer<'a, String> + 'a>;
+
+/// Create the scorer with the given name, `wins` or the name of an aggregation method.
+fn scorer<'a>(name: &str) -> Option<BoxedScorer<'a>> {
+    if name == "wins" {
+        Some(Box::new(Scores::new()))
+    } else {
+        name.parse::<Aggregation>()
+            .ok()
+            .map(|aggregation| Box::new(Aggregated::new(aggregation)) as BoxedScorer<'a>)
+    }
 }
 
 #[self_referencing]
@@ -64,15 +87,13 @@ struct OngoingComparison {
     comparisons: Comparisons<'this, String>,
     #[borrows(comparisons)]
     #[not_covariant]
-    iterator: RetainItemIterator<'this, String>,
-    #[borrows()]
-    #[covariant]
-    scores: Scores<'this, String>,
+    session: Session<'this, String, RetainWinner, BoxedScorer<'this>>,
 }
 
 thread_local! {
     static PUSHED_ITEMS: RefCell<IndexSet<Item>> = RefCell::new(IndexSet::new());
-    static ONGOING_COMPARISON: RefCell<Option<OngoingComparison>> = RefCell::new(None);
+    static ONGOING_COMPARISON: RefCell<Option<OngoingComparison>> = const { RefCell::new(None) };
+    static SCORER: RefCell<String> = RefCell::new("wins".to_owned());
 }
 
 fn pushed_items<F, R>(action: F) -> R
@@ -139,10 +160,11 @@ pub fn start_comparison() {
                 comparisons_builder: |items: &HashMap<u64, impaired::Item<String>>| {
                     Comparisons::new(items.values())
                 },
-                iterator_builder: |comparisons: &Comparisons<String>| {
-                    comparisons.retain_item_iterator()
+                session_builder: |comparisons: &Comparisons<String>| {
+                    let name = SCORER.with(|scorer| scorer.borrow().clone());
+                    Session::new(comparisons)
+                        .scorer(scorer(&name).expect("the selected scorer has to exist"))
                 },
-                scores: Scores::new(),
             }
             .build(),
         )
@@ -159,10 +181,10 @@ pub fn next_comparison() -> Option<Comparison> {
     if !has_ongoing_comparison() {
         start_comparison();
     }
-    ongoing_comparison_mut(|ongoing_comparison| {
-        ongoing_comparison.as_mut().and_then(|ongoing_comparison| {
-            ongoing_comparison.with_iterator_mut(|iterator| {
-                iterator.next().map(|(comparison, _)| Comparison {
+    ongoing_comparison(|ongoing_comparison| {
+        ongoing_comparison.as_ref().and_then(|ongoing_comparison| {
+            ongoing_comparison.with_session(|session| {
+                session.current_question().map(|comparison| Comparison {
                     left: Item {
                         hash: hash_one(comparison.left),
                         item: comparison.left.0.to_owned(),
@@ -182,33 +204,97 @@ pub fn track_result(winner: Item, loser: Item) {
     ongoing_comparison_mut(|ongoing_comparison| {
         if let Some(ongoing_comparison) = ongoing_comparison.as_mut() {
             ongoing_comparison.with_mut(|fields| {
-                if let (Some(winner), Some(loser)) = (
+                if let (Some(winner), Some(loser), Some(comparison)) = (
                     fields.items.get(&winner.hash),
                     fields.items.get(&loser.hash),
+                    fields.session.current_question(),
                 ) {
-                    fields.iterator.winner(winner);
-                    fields.scores.track(winner, loser);
+                    let outcome = if (comparison.left, comparison.right) == (winner, loser) {
+                        Outcome::Left
+                    } else if (comparison.right, comparison.left) == (winner, loser) {
+                        Outcome::Right
+                    } else {
+                        // The result does not belong to the current question.
+                        return;
+                    };
+                    // The question has just been checked to exist, so answering cannot fail.
+                    let _ = fields.session.answer(outcome);
                 }
             })
         }
     });
 }
 
+#[wasm_bindgen(js_name = getScorers)]
+pub fn get_scorers() -> Vec<String> {
+    std::iter::once("wins".to_owned())
+        .chain(
+            Aggregation::ALL
+                .iter()
+                .map(|aggregation| aggregation.to_string()),
+        )
+        .collect()
+}
+
+#[wasm_bindgen(js_name = setScorer)]
+pub fn set_scorer(name: String) -> bool {
+    if scorer(&name).is_none() {
+        return false;
+    }
+    ongoing_comparison_mut(|ongoing_comparison| {
+        if let Some(ongoing_comparison) = ongoing_comparison.as_mut() {
+            ongoing_comparison.with_session_mut(|session| {
+                if let Some(scorer) = scorer(&name) {
+                    session.set_scorer(scorer);
+                }
+            });
+        }
+    });
+    SCORER.with(|scorer| scorer.replace(name));
+    true
+}
+
 #[wasm_bindgen(js_name = getScores)]
 pub fn get_scores() -> Result<JsValue, serde_wasm_bindgen::Error> {
     ongoing_comparison(|ongoing_comparison| {
         let mut results = Vec::new();
         if let Some(ongoing_comparison) = ongoing_comparison {
-            let scores: &Scores<String> = ongoing_comparison.borrow_scores();
-            for (item, score) in scores.iter() {
+            // The ranking is ordered from best to worst, tied items share their rank.
+            let ranking = ongoing_comparison.with_session(|session| session.ranking());
+            for ranked in &ranking {
                 results.push(Score {
-                    item: Item::new(item.0.clone()),
-                    score: *score as u32,
+                    item: Item::new(ranked.item.0.clone()),
+                    score: ranked.score,
+                    rank: ranked.rank as u32,
                 });
             }
         }
 
-        (&results).serialize(&Serializer::new().serialize_large_number_types_as_bigints(true))
+        results.serialize(&Serializer::new().serialize_large_number_types_as_bigints(true))
+    })
+}
+
+#[wasm_bindgen(js_name = getCharts)]
+pub fn get_charts() -> Option<Charts> {
+    let aggregation = SCORER.with(|scorer| match scorer.borrow().as_str() {
+        "wins" => Aggregation::SumOfWins,
+        name => name.parse().unwrap_or(Aggregation::SumOfWins),
+    });
+    ongoing_comparison(|ongoing_comparison| {
+        ongoing_comparison.as_ref().map(|ongoing_comparison| {
+            ongoing_comparison.with_session(|session| {
+                let ranked: Vec<_> = session.ranking().iter().map(|ranked| ranked.item).collect();
+                let model = BradleyTerry::fit(ranked.iter().copied(), session.results());
+                // Fewer resamples than by default keep the page responsive.
+                let intervals = Bootstrap::new(ranked.iter().copied(), session.results())
+                    .resamples(200)
+                    .run(aggregation);
+                Charts {
+                    heatmap: svg::heatmap(&ranked, |a, b| model.probability(a, b)),
+                    scores: svg::score_chart(&intervals, aggregation),
+                }
+            })
+        })
     })
 }
 
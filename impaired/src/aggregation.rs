@@ -0,0 +1,625 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+//! Turn pairwise comparison results into a final ranking.
+//!
+//! Simply counting wins, as [`Scores`](crate::Scores) does, ignores whom an item won against and
+//! how the individual pairs turned out. The methods provided through [`Aggregation`](Aggregation)
+//! take this into account in different ways, which might match what you consider a fair result
+//! better.
+//!
+//! The results can stem from a single voter or from multiple voters, e.g. the
+//! [`votes`](crate::Panel::votes) of a [`Panel`](crate::Panel).
+//!
+//! ```rust
+//! # use impaired::{aggregation, Aggregation, ComparisonResult, Item};
+//! let rust = Item("Rust");
+//! let cpp = Item("C++");
+//! let java = Item("Java");
+//!
+//! let results = [
+//!     ComparisonResult::new(&rust, &cpp),
+//!     ComparisonResult::new(&rust, &cpp),
+//!     ComparisonResult::new(&cpp, &rust),
+//!     ComparisonResult::new(&java, &rust),
+//!     ComparisonResult::new(&cpp, &java),
+//!     ComparisonResult::new(&cpp, &java),
+//! ];
+//! let ranking = aggregation::aggregate(Aggregation::Schulze, [&rust, &cpp, &java], results);
+//! // Rust beats C++, which beats Java, which beats Rust, but Rust's win is the clearest.
+//! assert_eq!(ranking[0], (&rust, 2.0));
+//! ```
+
+use crate::{BradleyTerry, ComparisonResult, Item, Ranks};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Formatter},
+    hash::Hash,
+    str::FromStr,
+};
+
+/// The largest number of items for which [`Aggregation::Kemeny`](Aggregation::Kemeny) determines
+/// the optimal ranking exactly.
+pub const KEMENY_EXACT_LIMIT: usize = 16;
+
+/// A method to aggregate pairwise comparison results into a single ranking.
+///
+/// Every method assigns each item a score, the ranking is then given by ordering the items by their
+/// scores. Unless noted otherwise, a higher score is better.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregation {
+    /// Sum up the wins of every item across all results, i.e. the [`Scores`](crate::Scores) over
+    /// all results.
+    ///
+    /// The score of an item is its total number of wins.
+    SumOfWins,
+    /// Fit a [`BradleyTerry`](BradleyTerry) model over all results.
+    ///
+    /// The score of an item is its estimated strength.
+    BradleyTerry,
+    /// Rank the items for every voter individually by their number of wins, then order the items by
+    /// their rank averaged across all voters.
+    ///
+    /// The score of an item is its mean rank, where `1` is the best rank. Items with tied scores
+    /// share the average of the ranks they span. Contrary to the other methods, a lower score is
+    /// thus better.
+    ///
+    /// Only a [`Panel`](crate::Panel) knows which voter cast which vote. If the results are
+    /// aggregated through [`aggregate`](aggregate) instead, all of them are treated as coming from
+    /// a single voter.
+    MeanRank,
+    /// Compare every pair of items by the majority of their results: an item scores `1` for every
+    /// other item it beat more often than it lost against, and `0.5` for every other item it beat
+    /// exactly as often.
+    ///
+    /// Pairs of items that were never compared do not contribute to the score.
+    Copeland,
+    /// Award an item its share of wins against every other item it was compared with.
+    ///
+    /// This is the Borda count expressed through pairwise comparisons: if every pair of items was
+    /// compared exactly once, the score of an item is its number of wins. Contrary to
+    /// [`Aggregation::SumOfWins`](Aggregation::SumOfWins), pairs that were compared more often do
+    /// not weigh more.
+    Borda,
+    /// The Schulze method, which determines the strength of the strongest path of majority wins
+    /// from every item to every other item.
+    ///
+    /// The score of an item is the number of other items its strongest path to is stronger than
+    /// theirs in return. The Schulze method always ranks a Condorcet winner, i.e. an item beating
+    /// every other item by majority, first.
+    Schulze,
+    /// The Kemeny-Young method, which finds the ranking disagreeing with the fewest results.
+    ///
+    /// The score of an item is the number of items ranked below it. For up to
+    /// [`KEMENY_EXACT_LIMIT`](KEMENY_EXACT_LIMIT) items the optimal ranking is determined exactly.
+    /// Because the problem is NP-hard, a local search starting from the
+    /// [Copeland](Aggregation::Copeland) ranking is used for more items, which is not guaranteed to
+    /// find the optimum.
+    Kemeny,
+}
+
+impl Aggregation {
+    /// All aggregation methods.
+    pub const ALL: [Aggregation; 7] = [
+        Aggregation::SumOfWins,
+        Aggregation::BradleyTerry,
+        Aggregation::MeanRank,
+        Aggregation::Copeland,
+        Aggregation::Borda,
+        Aggregation::Schulze,
+        Aggregation::Kemeny,
+    ];
+
+    /// The name of the method in kebab-case, e.g. `sum-of-wins`, as accepted by
+    /// [`Aggregation::from_str`](Aggregation::from_str).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregation::SumOfWins => "sum-of-wins",
+            Aggregation::BradleyTerry => "bradley-terry",
+            Aggregation::MeanRank => "mean-rank",
+            Aggregation::Copeland => "copeland",
+            Aggregation::Borda => "borda",
+            Aggregation::Schulze => "schulze",
+            Aggregation::Kemeny => "kemeny",
+        }
+    }
+
+    /// Whether lower scores are better, which is the case for
+    /// [`Aggregation::MeanRank`](Aggregation::MeanRank) only.
+    pub fn lower_is_better(&self) -> bool {
+        matches!(self, Aggregation::MeanRank)
+    }
+
+    /// A score worse than any an item can reach through this aggregation, e.g. for items that
+    /// were not ranked at all.
+    pub(crate) fn worst_score(&self) -> f64 {
+        if self.lower_is_better() {
+            f64::INFINITY
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+}
+
+impl Display for Aggregation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Aggregation {
+    type Err = ParseAggregationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Aggregation::ALL
+            .into_iter()
+            .find(|aggregation| aggregation.name() == s)
+            .ok_or_else(|| ParseAggregationError(s.to_owned()))
+    }
+}
+
+/// The error returned when parsing an unknown [`Aggregation`](Aggregation) name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAggregationError(String);
+
+impl Display for ParseAggregationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown aggregation '{}', expected one of: {}",
+            self.0,
+            Aggregation::ALL
+                .map(|aggregation| aggregation.name())
+                .join(", ")
+        )
+    }
+}
+
+impl Error for ParseAggregationError {}
+
+/// Aggregate the results of pairwise comparisons into a single ranking using the chosen method.
+///
+/// Every item provided and every item that is part of the results are ranked, ordered from best to
+/// worst, along with their score according to the chosen [`Aggregation`](Aggregation). Items with
+/// equal scores are ordered ascending.
+pub fn aggregate<'a, T: 'a + Eq + Hash + Ord>(
+    aggregation: Aggregation,
+    items: impl IntoIterator<Item = &'a Item<T>>,
+    results: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+) -> Vec<(&'a Item<T>, f64)> {
+    let results: Vec<_> = results.into_iter().collect();
+    let matrix = PreferenceMatrix::new(items, &results);
+    let items = &matrix.items;
+
+    let scores: Vec<f64> = match aggregation {
+        Aggregation::SumOfWins => matrix.wins.iter().map(|row| row.iter().sum()).collect(),
+        Aggregation::BradleyTerry => {
+            let model = BradleyTerry::fit(items.iter().copied(), results);
+            items.iter().map(|item| model[item]).collect()
+        }
+        Aggregation::MeanRank => {
+            let ranks = Ranks::by_value(items, |item| matrix.wins[matrix.index[item]].iter().sum());
+            items.iter().map(|item| ranks[item]).collect()
+        }
+        Aggregation::Copeland => matrix.copeland(),
+        Aggregation::Borda => matrix.borda(),
+        Aggregation::Schulze => matrix.schulze(),
+        Aggregation::Kemeny => {
+            let order = if items.len() <= KEMENY_EXACT_LIMIT {
+                matrix.kemeny_exact()
+            } else {
+                matrix.kemeny_local_search()
+            };
+            let mut scores = vec![0.0; items.len()];
+            for (position, item) in order.into_iter().enumerate() {
+                scores[item] = (items.len() - position - 1) as f64;
+            }
+            scores
+        }
+    };
+
+    let mut ranking: Vec<_> = items.iter().copied().zip(scores).collect();
+    sort_ranking(aggregation, &mut ranking);
+    ranking
+}
+
+/// Sort the ranking from best to worst according to the aggregation, ordering items with equal
+/// scores ascending.
+pub(crate) fn sort_ranking<T: Ord>(aggregation: Aggregation, ranking: &mut [(&Item<T>, f64)]) {
+    ranking.sort_by(|(a_item, a), (b_item, b)| {
+        cmp_scores(aggregation, *a, *b).then_with(|| a_item.cmp(b_item))
+    });
+}
+
+/// Sort the ranking by descending scores, ordering items with equal scores ascending.
+pub(crate) fn sort_descending<T: Ord>(ranking: &mut [(&Item<T>, f64)]) {
+    ranking.sort_by(|(a_item, a), (b_item, b)| {
+        b.partial_cmp(a)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a_item.cmp(b_item))
+    });
+}
+
+/// Compare two scores according to the aggregation, such that the better score is ordered first.
+pub(crate) fn cmp_scores(aggregation: Aggregation, a: f64, b: f64) -> Ordering {
+    let ordering = if aggregation.lower_is_better() {
+        a.partial_cmp(&b)
+    } else {
+        b.partial_cmp(&a)
+    };
+    ordering.unwrap_or(Ordering::Equal)
+}
+
+/// How often every item won against every other item.
+struct PreferenceMatrix<'a, T> {
+    items: Vec<&'a Item<T>>,
+    index: HashMap<&'a Item<T>, usize>,
+    /// `wins[a][b]` is the number of times item `a` won against item `b`.
+    wins: Vec<Vec<f64>>,
+}
+
+impl<'a, T: Eq + Hash + Ord> PreferenceMatrix<'a, T> {
+    fn new(
+        items: impl IntoIterator<Item = &'a Item<T>>,
+        results: &[ComparisonResult<'a, T>],
+    ) -> Self {
+        let mut items: Vec<_> = items
+            .into_iter()
+            .chain(
+                results
+                    .iter()
+                    .flat_map(|result| [result.winner, result.loser]),
+            )
+            .collect();
+        items.sort_unstable();
+        items.dedup();
+        let index: HashMap<_, _> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (*item, index))
+            .collect();
+
+        let mut wins = vec![vec![0.0; items.len()]; items.len()];
+        for result in results {
+            wins[index[result.winner]][index[result.loser]] += 1.0;
+        }
+
+        Self { items, index, wins }
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn copeland(&self) -> Vec<f64> {
+        (0..self.len())
+            .map(|a| {
+                (0..self.len())
+                    .filter(|b| a != *b)
+                    .map(|b| match self.wins[a][b].partial_cmp(&self.wins[b][a]) {
+                        Some(Ordering::Greater) => 1.0,
+                        Some(Ordering::Equal) if self.wins[a][b] > 0.0 => 0.5,
+                        _ => 0.0,
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn borda(&self) -> Vec<f64> {
+        (0..self.len())
+            .map(|a| {
+                (0..self.len())
+                    .filter(|b| a != *b)
+                    .map(|b| {
+                        let compared = self.wins[a][b] + self.wins[b][a];
+                        if compared > 0.0 {
+                            self.wins[a][b] / compared
+                        } else {
+                            0.0
+                        }
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn schulze(&self) -> Vec<f64> {
+        let n = self.len();
+        // Only majority wins form the initial paths.
+        let mut strength: Vec<Vec<f64>> = (0..n)
+            .map(|a| {
+                (0..n)
+                    .map(|b| {
+                        if self.wins[a][b] > self.wins[b][a] {
+                            self.wins[a][b]
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        for via in 0..n {
+            for a in (0..n).filter(|a| *a != via) {
+                for b in (0..n).filter(|b| *b != via && *b != a) {
+                    let through_via: f64 = strength[a][via].min(strength[via][b]);
+                    if through_via > strength[a][b] {
+                        strength[a][b] = through_via;
+                    }
+                }
+            }
+        }
+
+        (0..n)
+            .map(|a| {
+                (0..n)
+                    .filter(|b| a != *b && strength[a][*b] > strength[*b][a])
+                    .count() as f64
+            })
+            .collect()
+    }
+
+    /// The number of results an ordering of item indices disagrees with.
+    #[cfg(test)]
+    fn disagreements(&self, order: &[usize]) -> f64 {
+        let mut disagreements = 0.0;
+        for (position, above) in order.iter().enumerate() {
+            for below in &order[position + 1..] {
+                disagreements += self.wins[*below][*above];
+            }
+        }
+        disagreements
+    }
+
+    /// Find the ordering with the fewest disagreements through dynamic programming over all subsets
+    /// of items, building the ordering from the top.
+    fn kemeny_exact(&self) -> Vec<usize> {
+        let n = self.len();
+        let subsets = 1usize << n;
+        let mut disagreements = vec![f64::INFINITY; subsets];
+        let mut last_placed = vec![0; subsets];
+        disagreements[0] = 0.0;
+
+        for placed in 0..subsets {
+            if disagreements[placed].is_infinite() {
+                continue;
+            }
+            for next in (0..n).filter(|next| placed & (1 << next) == 0) {
+                // Placing `next` below all items placed so far disagrees with every result in which
+                // it won against one of them.
+                let added: f64 = (0..n)
+                    .filter(|above| placed & (1 << above) != 0)
+                    .map(|above| self.wins[next][above])
+                    .sum();
+                let with_next = placed | (1 << next);
+                // Ties are broken towards placing items with smaller indices first.
+                if disagreements[placed] + added < disagreements[with_next] {
+                    disagreements[with_next] = disagreements[placed] + added;
+                    last_placed[with_next] = next;
+                }
+            }
+        }
+
+        let mut order = Vec::with_capacity(n);
+        let mut placed = subsets - 1;
+        while placed != 0 {
+            let last = last_placed[placed];
+            order.push(last);
+            placed &= !(1 << last);
+        }
+        order.reverse();
+        order
+    }
+
+    /// Improve the Copeland ordering by moving single items to the position that reduces the
+    /// disagreements the most, until no such move is left.
+    fn kemeny_local_search(&self) -> Vec<usize> {
+        const MAX_ROUNDS: usize = 100;
+
+        let copeland = self.copeland();
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by(|a, b| {
+            copeland[*b]
+                .partial_cmp(&copeland[*a])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        for _ in 0..MAX_ROUNDS {
+            let mut improved = false;
+            for from in 0..order.len() {
+                let item = order[from];
+                // Determine the change in disagreements for moving the item to every position.
+                let (mut best_to, mut best_change) = (from, 0.0);
+                let mut change = 0.0;
+                for to in (0..from).rev() {
+                    let passed = order[to];
+                    change += self.wins[passed][item] - self.wins[item][passed];
+                    if change < best_change {
+                        best_to = to;
+                        best_change = change;
+                    }
+                }
+                change = 0.0;
+                for (to, passed) in order.iter().enumerate().skip(from + 1) {
+                    change += self.wins[item][*passed] - self.wins[*passed][item];
+                    if change < best_change {
+                        best_to = to;
+                        best_change = change;
+                    }
+                }
+
+                if best_to != from {
+                    let item = order.remove(from);
+                    order.insert(best_to, item);
+                    improved = true;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn results_from_pairs<'a>(
+        items: &'a [Item<usize>],
+        pairs: &[(usize, usize, usize)],
+    ) -> Vec<ComparisonResult<'a, usize>> {
+        pairs
+            .iter()
+            .flat_map(|(winner, loser, count)| {
+                (0..*count).map(move |_| ComparisonResult::new(&items[*winner], &items[*loser]))
+            })
+            .collect()
+    }
+
+    fn order(ranking: Vec<(&Item<usize>, f64)>) -> Vec<usize> {
+        ranking.into_iter().map(|(item, _)| item.0).collect()
+    }
+
+    #[test]
+    fn condorcet_winner_is_ranked_first() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        // Item 2 beats everyone by majority, while 0 has the most wins in total.
+        let results = results_from_pairs(
+            &items,
+            &[
+                (2, 0, 2),
+                (0, 2, 1),
+                (2, 1, 1),
+                (2, 3, 1),
+                (0, 1, 5),
+                (0, 3, 5),
+            ],
+        );
+
+        assert_eq!(
+            order(aggregate(Aggregation::SumOfWins, &items, results.clone()))[0],
+            0
+        );
+        for aggregation in [
+            Aggregation::Copeland,
+            Aggregation::Schulze,
+            Aggregation::Kemeny,
+        ] {
+            let ranking = order(aggregate(aggregation, &items, results.clone()));
+            assert_eq!(ranking[0], 2, "{:?}", aggregation);
+        }
+    }
+
+    #[test]
+    fn schulze_resolves_cycles_by_strength() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        // 0 beats 1 clearly, 1 beats 2 clearly, 2 beats 0 narrowly.
+        let results = results_from_pairs(
+            &items,
+            &[
+                (0, 1, 5),
+                (1, 0, 1),
+                (1, 2, 4),
+                (2, 1, 1),
+                (2, 0, 3),
+                (0, 2, 2),
+            ],
+        );
+
+        assert_eq!(
+            order(aggregate(Aggregation::Schulze, &items, results.clone())),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            order(aggregate(Aggregation::Kemeny, &items, results)),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn kemeny_exact_finds_the_optimum() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let results = results_from_pairs(
+            &items,
+            &[
+                (0, 1, 2),
+                (1, 0, 1),
+                (1, 2, 3),
+                (2, 3, 1),
+                (3, 2, 2),
+                (3, 4, 4),
+                (4, 0, 3),
+                (0, 4, 1),
+                (2, 0, 2),
+                (1, 4, 1),
+            ],
+        );
+        let matrix = PreferenceMatrix::new(&items, &results);
+
+        let exact = matrix.disagreements(&matrix.kemeny_exact());
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        let mut optimum = f64::INFINITY;
+        permutations(&mut order, 0, &mut |order| {
+            optimum = optimum.min(matrix.disagreements(order));
+        });
+        assert_eq!(exact, optimum);
+        assert!(matrix.disagreements(&matrix.kemeny_local_search()) >= exact);
+    }
+
+    fn permutations(order: &mut [usize], start: usize, visit: &mut impl FnMut(&[usize])) {
+        if start == order.len() {
+            visit(order);
+            return;
+        }
+        for index in start..order.len() {
+            order.swap(start, index);
+            permutations(order, start + 1, visit);
+            order.swap(start, index);
+        }
+    }
+
+    #[test]
+    fn kemeny_local_search_recovers_consistent_orders() {
+        let items: Vec<Item<usize>> = (0..KEMENY_EXACT_LIMIT + 4).map(Item).collect();
+        let mut pairs = Vec::new();
+        for a in 0..items.len() {
+            for b in 0..a {
+                pairs.push((a, b, 1));
+            }
+        }
+        let results = results_from_pairs(&items, &pairs);
+
+        let ranking = order(aggregate(Aggregation::Kemeny, &items, results));
+        assert_eq!(ranking, (0..items.len()).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for aggregation in Aggregation::ALL {
+            assert_eq!(aggregation.to_string().parse(), Ok(aggregation));
+        }
+        assert!("wins".parse::<Aggregation>().is_err());
+    }
+
+    #[test]
+    fn borda_normalizes_repeated_comparisons() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let results = results_from_pairs(&items, &[(0, 1, 10), (1, 0, 10), (1, 2, 1)]);
+
+        let ranking = aggregate(Aggregation::Borda, &items, results.clone());
+        assert_eq!(ranking[0], (&items[1], 1.5));
+        let ranking = aggregate(Aggregation::SumOfWins, &items, results);
+        assert_eq!(ranking[0], (&items[1], 11.0));
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+//! Statistics on how much multiple rankings of the same items agree with each other.
+//!
+//! The statistics operate on [`Ranks`](Ranks), which can be derived from [`Scores`](Scores) or
+//! from any ordering of items. [`pairwise_agreement`](pairwise_agreement) additionally allows
+//! comparing two sets of raw [`ComparisonResult`s](ComparisonResult) directly.
+//!
+//! ```rust
+//! # use impaired::{agreement, Item, Ranks};
+//! let rust = Item("Rust");
+//! let cpp = Item("C++");
+//! let java = Item("Java");
+//!
+//! let alice = Ranks::from_ordering([&rust, &cpp, &java]);
+//! let bob = Ranks::from_ordering([&rust, &java, &cpp]);
+//! assert_eq!(agreement::kendall_tau(&alice, &bob), Some(1.0 / 3.0));
+//! assert_eq!(agreement::spearman_rho(&alice, &bob), Some(0.5));
+//!
+//! let disagreement = agreement::disagreement(&[alice, bob]);
+//! assert_eq!(disagreement[0].1, 0.5);
+//! assert_eq!(disagreement[2], (&rust, 0.0));
+//! ```
+
+use crate::{Comparison, ComparisonResult, Item, Scores};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::Deref,
+};
+
+/// The ranks of items within a ranking, where `1` is the best rank.
+///
+/// Tied items share the average of the ranks they span, e.g. two items tied for first place both
+/// have a rank of `1.5`.
+///
+/// `Ranks` dereferences into a [`HashMap`](std::collections::HashMap) mapping every
+/// [`Item`](Item) to its rank.
+#[derive(Debug)]
+pub struct Ranks<'a, T>(HashMap<&'a Item<T>, f64>);
+
+impl<'a, T> Clone for Ranks<'a, T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Ranks<'a, T> {
+    /// Rank the items by their scores, the item with the highest score being ranked first.
+    ///
+    /// ```rust
+    /// # use impaired::{Item, Ranks, Scores};
+    /// let rust = Item("Rust");
+    /// let cpp = Item("C++");
+    /// let java = Item("Java");
+    ///
+    /// let mut scores = Scores::new();
+    /// scores.track(&rust, &cpp);
+    /// scores.track(&rust, &java);
+    ///
+    /// let ranks = Ranks::from_scores(&scores);
+    /// assert_eq!(ranks[&rust], 1.0);
+    /// assert_eq!(ranks[&cpp], 2.5);
+    /// assert_eq!(ranks[&java], 2.5);
+    /// ```
+    pub fn from_scores(scores: &Scores<'a, T>) -> Self {
+        let items: Vec<_> = scores.keys().copied().collect();
+        Self::from_scores_of(&items, scores)
+    }
+
+    /// Rank the items in the order provided, the first item being ranked first.
+    ///
+    /// There are no ties in the resulting ranks.
+    pub fn from_ordering(items: impl IntoIterator<Item = &'a Item<T>>) -> Self {
+        Self(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(position, item)| (item, (position + 1) as f64))
+                .collect(),
+        )
+    }
+
+    /// Rank the provided items by their scores, treating items without a score as having no wins.
+    pub(crate) fn from_scores_of(items: &[&'a Item<T>], scores: &Scores<'a, T>) -> Self {
+        Self::by_value(items, |item| {
+            scores.get(item).copied().unwrap_or_default() as f64
+        })
+    }
+
+    /// Rank the provided items by a value, the item with the highest value being ranked first.
+    pub(crate) fn by_value(items: &[&'a Item<T>], value: impl Fn(&Item<T>) -> f64) -> Self {
+        let mut sorted: Vec<_> = items.iter().map(|item| (*item, value(item))).collect();
+        sorted.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        let mut ranks = HashMap::with_capacity(sorted.len());
+        let mut start = 0;
+        while start < sorted.len() {
+            let (_, start_value) = sorted[start];
+            let end = start
+                + sorted[start..]
+                    .iter()
+                    .take_while(|(_, value)| *value == start_value)
+                    .count();
+            // Positions `start..end` are zero-based, ranks are one-based.
+            let rank = (start + end + 1) as f64 / 2.0;
+            ranks.extend(sorted[start..end].iter().map(|(item, _)| (*item, rank)));
+            start = end;
+        }
+        Self(ranks)
+    }
+}
+
+impl<'a, T> Deref for Ranks<'a, T> {
+    type Target = HashMap<&'a Item<T>, f64>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+fn common_items<'a, T: Eq + Hash + Ord>(rankings: &[&Ranks<'a, T>]) -> Vec<&'a Item<T>> {
+    let mut items: Vec<_> = match rankings.split_first() {
+        Some((first, rest)) => first
+            .keys()
+            .filter(|item| rest.iter().all(|ranks| ranks.contains_key(*item)))
+            .copied()
+            .collect(),
+        None => Vec::new(),
+    };
+    items.sort_unstable();
+    items
+}
+
+/// Kendall's tau-b rank correlation between two rankings.
+///
+/// The correlation is `1` if both rankings order every pair of items the same way, `-1` if they
+/// order every pair the opposite way, and around `0` if they are unrelated. Ties are accounted for.
+///
+/// Only items that are part of both rankings are considered. `None` is returned if there are fewer
+/// than two such items, or if either ranking ties all of them.
+pub fn kendall_tau<T: Eq + Hash + Ord>(a: &Ranks<T>, b: &Ranks<T>) -> Option<f64> {
+    let items = common_items(&[a, b]);
+    let (mut concordant, mut discordant, mut tied_a, mut tied_b): (f64, f64, f64, f64) =
+        (0.0, 0.0, 0.0, 0.0);
+    for (index, first) in items.iter().enumerate() {
+        for second in &items[index + 1..] {
+            let order_a = a[first].partial_cmp(&a[second]);
+            let order_b = b[first].partial_cmp(&b[second]);
+            match (order_a, order_b) {
+                (Some(Ordering::Equal), Some(Ordering::Equal)) => {}
+                (Some(Ordering::Equal), _) => tied_a += 1.0,
+                (_, Some(Ordering::Equal)) => tied_b += 1.0,
+                (order_a, order_b) if order_a == order_b => concordant += 1.0,
+                _ => discordant += 1.0,
+            }
+        }
+    }
+
+    let denominator: f64 =
+        ((concordant + discordant + tied_a) * (concordant + discordant + tied_b)).sqrt();
+    if denominator == 0.0 {
+        None
+    } else {
+        Some((concordant - discordant) / denominator)
+    }
+}
+
+/// Spearman's rank correlation between two rankings.
+///
+/// This is the Pearson correlation of the ranks, ranging from `-1` to `1` like
+/// [`kendall_tau`](kendall_tau), but weighing pairs by how far apart their items are ranked.
+///
+/// Only items that are part of both rankings are considered. `None` is returned if there are fewer
+/// than two such items, or if either ranking ties all of them.
+pub fn spearman_rho<T: Eq + Hash + Ord>(a: &Ranks<T>, b: &Ranks<T>) -> Option<f64> {
+    let items = common_items(&[a, b]);
+    if items.len() < 2 {
+        return None;
+    }
+
+    let count = items.len() as f64;
+    let mean_a = items.iter().map(|item| a[item]).sum::<f64>() / count;
+    let mean_b = items.iter().map(|item| b[item]).sum::<f64>() / count;
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for item in &items {
+        let deviation_a = a[item] - mean_a;
+        let deviation_b = b[item] - mean_b;
+        covariance += deviation_a * deviation_b;
+        variance_a += deviation_a * deviation_a;
+        variance_b += deviation_b * deviation_b;
+    }
+
+    let denominator: f64 = (variance_a * variance_b).sqrt();
+    if denominator == 0.0 {
+        None
+    } else {
+        Some(covariance / denominator)
+    }
+}
+
+/// Kendall's coefficient of concordance (Kendall's W) across any number of rankings.
+///
+/// The coefficient ranges from `0`, no agreement at all, to `1`, all rankings are identical. Ties
+/// are accounted for.
+///
+/// Only items that are part of every ranking are considered. `None` is returned if there are fewer
+/// than two rankings or fewer than two such items, or if every ranking ties all of them.
+pub fn kendalls_w<T: Eq + Hash + Ord>(rankings: &[Ranks<T>]) -> Option<f64> {
+    let rankings: Vec<_> = rankings.iter().collect();
+    let items = common_items(&rankings);
+    if rankings.len() < 2 || items.len() < 2 {
+        return None;
+    }
+
+    // The ranks are re-derived on the common items only, such that every ranking ranks the same
+    // items from `1` to `n`.
+    let rankings: Vec<Ranks<T>> = rankings
+        .iter()
+        .map(|ranks| Ranks::by_value(&items, |item| -ranks[item]))
+        .collect();
+
+    let voters = rankings.len() as f64;
+    let count = items.len() as f64;
+    let rank_sums: Vec<f64> = items
+        .iter()
+        .map(|item| rankings.iter().map(|ranks| ranks[item]).sum())
+        .collect();
+    let mean_rank_sum = voters * (count + 1.0) / 2.0;
+    let deviations: f64 = rank_sums
+        .iter()
+        .map(|rank_sum| (rank_sum - mean_rank_sum).powi(2))
+        .sum();
+
+    let ties: f64 = rankings
+        .iter()
+        .map(|ranks| {
+            let mut group_sizes: HashMap<u64, f64> = HashMap::new();
+            for rank in ranks.values() {
+                *group_sizes.entry(rank.to_bits()).or_default() += 1.0;
+            }
+            group_sizes
+                .values()
+                .map(|size| size.powi(3) - size)
+                .sum::<f64>()
+        })
+        .sum();
+
+    let denominator = voters.powi(2) * (count.powi(3) - count) - voters * ties;
+    if denominator == 0.0 {
+        None
+    } else {
+        Some(12.0 * deviations / denominator)
+    }
+}
+
+/// How much the rankings disagree on every item, from the most to the least disputed item.
+///
+/// The disagreement of an item is the standard deviation of its ranks across all rankings that
+/// contain it. A disagreement of `0` means every ranking ranks the item the same.
+pub fn disagreement<'a, T: Eq + Hash + Ord>(rankings: &[Ranks<'a, T>]) -> Vec<(&'a Item<T>, f64)> {
+    let mut ranks_by_item: HashMap<&'a Item<T>, Vec<f64>> = HashMap::new();
+    for ranks in rankings {
+        for (item, rank) in ranks.iter() {
+            ranks_by_item.entry(*item).or_default().push(*rank);
+        }
+    }
+
+    let mut disagreement: Vec<_> = ranks_by_item
+        .into_iter()
+        .map(|(item, ranks)| {
+            let count = ranks.len() as f64;
+            let mean = ranks.iter().sum::<f64>() / count;
+            let variance = ranks.iter().map(|rank| (rank - mean).powi(2)).sum::<f64>() / count;
+            (item, variance.sqrt())
+        })
+        .collect();
+    disagreement.sort_by(|(a_item, a), (b_item, b)| {
+        b.partial_cmp(a)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a_item.cmp(b_item))
+    });
+    disagreement
+}
+
+/// How much two sets of raw comparison results agree on the comparisons they have in common.
+///
+/// This is the share of common comparisons with the same winner minus the share of common
+/// comparisons with different winners, i.e. Kendall's tau restricted to the comparisons both sets
+/// contain. It is useful when the results are too sparse to derive meaningful rankings from, e.g.
+/// if every voter only answered a few comparisons.
+///
+/// If a comparison appears multiple times within one set, its last result is used. `None` is
+/// returned if the sets have no comparison in common.
+///
+/// ```rust
+/// # use impaired::{agreement, ComparisonResult, Item};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let java = Item("Java");
+///
+/// let alice = [
+///     ComparisonResult::new(&rust, &cpp),
+///     ComparisonResult::new(&java, &cpp),
+/// ];
+/// let bob = [
+///     ComparisonResult::new(&rust, &cpp),
+///     ComparisonResult::new(&cpp, &java),
+///     ComparisonResult::new(&rust, &java),
+/// ];
+/// assert_eq!(agreement::pairwise_agreement(alice, bob), Some(0.0));
+/// ```
+pub fn pairwise_agreement<'a, T: 'a + Eq + Hash + Ord>(
+    a: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+    b: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+) -> Option<f64> {
+    fn winners<'a, T: 'a + Eq + Hash + Ord>(
+        results: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+    ) -> HashMap<Comparison<'a, T>, &'a Item<T>> {
+        results
+            .into_iter()
+            .map(|result| (result.comparison, result.winner))
+            .collect()
+    }
+
+    let a = winners(a);
+    let b = winners(b);
+    let common: HashSet<_> = a
+        .keys()
+        .filter(|comparison| b.contains_key(*comparison))
+        .collect();
+    if common.is_empty() {
+        return None;
+    }
+
+    let agreeing = common
+        .iter()
+        .filter(|comparison| a[**comparison] == b[**comparison])
+        .count() as f64;
+    let total = common.len() as f64;
+    Some((2.0 * agreeing - total) / total)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn identical_rankings_agree_fully() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let ranks = Ranks::from_ordering(items.iter());
+
+        assert_eq!(kendall_tau(&ranks, &ranks), Some(1.0));
+        assert_eq!(spearman_rho(&ranks, &ranks), Some(1.0));
+        assert_eq!(kendalls_w(&[ranks.clone(), ranks.clone()]), Some(1.0));
+        assert!(disagreement(&[ranks.clone(), ranks])
+            .iter()
+            .all(|(_, disagreement)| *disagreement == 0.0));
+    }
+
+    #[test]
+    fn reversed_rankings_disagree_fully() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let ranks = Ranks::from_ordering(items.iter());
+        let reversed = Ranks::from_ordering(items.iter().rev());
+
+        assert_eq!(kendall_tau(&ranks, &reversed), Some(-1.0));
+        assert_eq!(spearman_rho(&ranks, &reversed), Some(-1.0));
+        assert_eq!(kendalls_w(&[ranks, reversed]), Some(0.0));
+    }
+
+    #[test]
+    fn ties_are_accounted_for() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut scores = Scores::new();
+        scores.track(&items[0], &items[1]);
+        scores.track(&items[0], &items[2]);
+        let tied = Ranks::from_scores(&scores);
+        let ordered = Ranks::from_ordering(items.iter());
+
+        // Two concordant pairs, the tied pair only counts against the tied ranking.
+        let tau = kendall_tau(&tied, &ordered).unwrap();
+        assert!((tau - 2.0 / (2.0f64 * 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(kendalls_w(&[tied.clone(), tied]), Some(1.0));
+    }
+
+    #[test]
+    fn only_common_items_are_considered() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let a = Ranks::from_ordering(items[..3].iter());
+        let b = Ranks::from_ordering(items[1..].iter());
+
+        assert_eq!(kendall_tau(&a, &b), Some(1.0));
+        assert_eq!(kendall_tau(&a, &Ranks::from_ordering([&items[3]])), None);
+    }
+
+    #[test]
+    fn pairwise_agreement_uses_the_last_result() {
+        let item1 = Item(1);
+        let item2 = Item(2);
+        let agreement = pairwise_agreement(
+            [
+                ComparisonResult::new(&item1, &item2),
+                ComparisonResult::new(&item2, &item1),
+            ],
+            [ComparisonResult::new(&item2, &item1)],
+        );
+        assert_eq!(agreement, Some(1.0));
+        assert_eq!(
+            pairwise_agreement([ComparisonResult::new(&item1, &item2)], []),
+            None
+        );
+    }
+}
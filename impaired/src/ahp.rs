@@ -0,0 +1,316 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{aggregation, Comparison, Comparisons, Item};
+use std::{collections::HashMap, hash::Hash};
+
+/// Saaty's random consistency index for matrices of size 1 to 15.
+///
+/// Larger matrices use the index of size 15.
+const RANDOM_INDEX: [f64; 15] = [
+    0.0, 0.0, 0.58, 0.90, 1.12, 1.24, 1.32, 1.41, 1.45, 1.49, 1.51, 1.48, 1.56, 1.57, 1.59,
+];
+
+/// Collect judgements on comparisons and derive priorities from them through the Analytic Hierarchy
+/// Process (AHP).
+///
+/// Instead of only choosing the preferred item, every comparison is answered with how strongly the
+/// preferred item is preferred, using Saaty's scale:
+///
+/// | Intensity  | Meaning                                        |
+/// |------------|------------------------------------------------|
+/// | 1          | Both items are equally important               |
+/// | 3          | The item is moderately more important          |
+/// | 5          | The item is strongly more important            |
+/// | 7          | The item is very strongly more important       |
+/// | 9          | The item is extremely more important           |
+/// | 2, 4, 6, 8 | Intermediate values between the above          |
+///
+/// The judgements fill a reciprocal pairwise matrix, from which the priorities of the items are
+/// derived as its principal eigenvector. Since judgements are rarely perfectly consistent (if `a`
+/// is twice as important as `b`, and `b` twice as important as `c`, `a` should be four times as
+/// important as `c`), the consistency ratio of the matrix is reported as well. A ratio above
+/// [`Priorities::CONSISTENCY_THRESHOLD`](Priorities::CONSISTENCY_THRESHOLD) indicates judgements
+/// that should be revisited.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Ahp, Comparisons, Item};
+/// let security = Item("Security");
+/// let cost = Item("Cost");
+/// let speed = Item("Speed");
+///
+/// let comparisons = Comparisons::new([&security, &cost, &speed]);
+/// let mut ahp = Ahp::new(&comparisons);
+/// ahp.judge(&security, &cost, 3);
+/// ahp.judge(&security, &speed, 5);
+/// ahp.judge(&cost, &speed, 2);
+/// assert!(ahp.next_comparison().is_none());
+///
+/// let priorities = ahp.priorities();
+/// assert_eq!(priorities.ranking[0].0, &security);
+/// assert!(priorities.is_consistent());
+/// assert!(priorities.warning().is_none());
+/// ```
+#[derive(Debug)]
+pub struct Ahp<'a, T: Eq + Hash + Ord> {
+    comparisons: Vec<Comparison<'a, T>>,
+    items: Vec<&'a Item<T>>,
+    index: HashMap<&'a Item<T>, usize>,
+    judgements: HashMap<Comparison<'a, T>, (&'a Item<T>, u8)>,
+}
+
+impl<'a, T: Eq + Hash + Ord> Ahp<'a, T> {
+    /// Prepare judging the given comparisons.
+    pub fn new(comparisons: &Comparisons<'a, T>) -> Self {
+        let items = comparisons.items();
+        let index = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (*item, index))
+            .collect();
+        let mut sorted: Vec<_> = comparisons.iter().collect();
+        sorted.sort_by(|a, b| {
+            (a.left.min(a.right), a.left.max(a.right))
+                .cmp(&(b.left.min(b.right), b.left.max(b.right)))
+        });
+        Self {
+            comparisons: sorted,
+            items,
+            index,
+            judgements: HashMap::new(),
+        }
+    }
+
+    /// The next comparison that has not been judged yet, if any.
+    ///
+    /// Comparisons are returned in a deterministic order.
+    pub fn next_comparison(&self) -> Option<Comparison<'a, T>> {
+        self.comparisons
+            .iter()
+            .find(|comparison| !self.judgements.contains_key(comparison))
+            .copied()
+    }
+
+    /// Judge that `preferred` is more important than `other` with the given intensity on Saaty's
+    /// scale, replacing any previous judgement of the two items.
+    ///
+    /// An intensity of `1` judges both items as equally important, regardless of their order.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the intensity is not within `1..=9`, if both items are the same, or if either item
+    /// is not part of the comparisons.
+    pub fn judge(&mut self, preferred: &'a Item<T>, other: &'a Item<T>, intensity: u8) {
+        assert!(
+            (1..=9).contains(&intensity),
+            "the intensity has to be within 1 and 9"
+        );
+        assert!(
+            preferred != other,
+            "an item cannot be judged against itself"
+        );
+        assert!(
+            self.index.contains_key(preferred) && self.index.contains_key(other),
+            "the items have to be part of the comparisons"
+        );
+        self.judgements
+            .insert(Comparison::new(preferred, other), (preferred, intensity));
+    }
+
+    /// Whether every comparison has been judged.
+    pub fn is_complete(&self) -> bool {
+        self.next_comparison().is_none()
+    }
+
+    /// The reciprocal pairwise matrix of the judgements, with rows and columns in the order of the
+    /// items.
+    ///
+    /// The entry in row `i` and column `j` states how much more important item `i` is than item
+    /// `j`. Comparisons that have not been judged yet are considered equally important.
+    pub fn matrix(&self) -> (Vec<&'a Item<T>>, Vec<Vec<f64>>) {
+        let size = self.items.len();
+        let mut matrix = vec![vec![1.0; size]; size];
+        for (comparison, (preferred, intensity)) in &self.judgements {
+            let preferred_index = self.index[preferred];
+            let other_index = self.index[comparison.other(preferred)];
+            matrix[preferred_index][other_index] = f64::from(*intensity);
+            matrix[other_index][preferred_index] = 1.0 / f64::from(*intensity);
+        }
+        (self.items.clone(), matrix)
+    }
+
+    /// Derive the priorities of the items from the judgements made so far.
+    pub fn priorities(&self) -> Priorities<'a, T> {
+        let (items, matrix) = self.matrix();
+        let size = items.len();
+        if size == 0 {
+            return Priorities {
+                ranking: Vec::new(),
+                lambda_max: 0.0,
+                consistency_index: 0.0,
+                consistency_ratio: 0.0,
+            };
+        }
+
+        // Power iteration converges quickly for positive matrices, which reciprocal matrices are.
+        let mut weights = vec![1.0 / size as f64; size];
+        for _ in 0..1000 {
+            let mut next: Vec<f64> = matrix
+                .iter()
+                .map(|row| row.iter().zip(&weights).map(|(a, w)| a * w).sum())
+                .collect();
+            let sum: f64 = next.iter().sum();
+            next.iter_mut().for_each(|weight| *weight /= sum);
+            let change: f64 = next
+                .iter()
+                .zip(&weights)
+                .map(|(next, weight)| (next - weight).abs())
+                .sum();
+            weights = next;
+            if change < 1e-12 {
+                break;
+            }
+        }
+
+        let lambda_max = matrix
+            .iter()
+            .zip(&weights)
+            .map(|(row, weight)| {
+                let product: f64 = row.iter().zip(&weights).map(|(a, w)| a * w).sum();
+                product / weight
+            })
+            .sum::<f64>()
+            / size as f64;
+        let consistency_index = if size > 2 {
+            ((lambda_max - size as f64) / (size as f64 - 1.0)).max(0.0)
+        } else {
+            0.0
+        };
+        let random_index = RANDOM_INDEX[(size - 1).min(RANDOM_INDEX.len() - 1)];
+        let consistency_ratio = if random_index > 0.0 {
+            consistency_index / random_index
+        } else {
+            0.0
+        };
+
+        let mut ranking: Vec<_> = items.into_iter().zip(weights).collect();
+        aggregation::sort_descending(&mut ranking);
+        Priorities {
+            ranking,
+            lambda_max,
+            consistency_index,
+            consistency_ratio,
+        }
+    }
+}
+
+/// The priorities derived from AHP judgements, along with their consistency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Priorities<'a, T> {
+    /// The items with their priorities, which sum up to `1`, ordered from highest to lowest.
+    pub ranking: Vec<(&'a Item<T>, f64)>,
+    /// The principal eigenvalue of the pairwise matrix.
+    pub lambda_max: f64,
+    /// The consistency index, `(lambda_max - n) / (n - 1)`.
+    pub consistency_index: f64,
+    /// The consistency index relative to that of random judgements.
+    pub consistency_ratio: f64,
+}
+
+impl<'a, T> Priorities<'a, T> {
+    /// The consistency ratio above which judgements are considered inconsistent.
+    pub const CONSISTENCY_THRESHOLD: f64 = 0.1;
+
+    /// Whether the consistency ratio is at most
+    /// [`CONSISTENCY_THRESHOLD`](Priorities::CONSISTENCY_THRESHOLD).
+    pub fn is_consistent(&self) -> bool {
+        self.consistency_ratio <= Self::CONSISTENCY_THRESHOLD
+    }
+
+    /// A warning to show to the voters if the judgements are inconsistent.
+    pub fn warning(&self) -> Option<String> {
+        if self.is_consistent() {
+            None
+        } else {
+            Some(format!(
+                "the judgements are inconsistent (consistency ratio {:.3} exceeds {}), consider \
+                 revisiting them",
+                self.consistency_ratio,
+                Self::CONSISTENCY_THRESHOLD
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn perfectly_consistent_judgements() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut ahp = Ahp::new(&comparisons);
+        ahp.judge(&items[0], &items[1], 2);
+        ahp.judge(&items[1], &items[2], 2);
+        ahp.judge(&items[0], &items[2], 4);
+
+        let priorities = ahp.priorities();
+        let weights: Vec<_> = priorities
+            .ranking
+            .iter()
+            .map(|(item, weight)| (item.0, (weight * 7.0).round()))
+            .collect();
+        assert_eq!(weights, vec![(0, 4.0), (1, 2.0), (2, 1.0)]);
+        assert!((priorities.lambda_max - 3.0).abs() < 1e-9);
+        assert!(priorities.consistency_ratio < 1e-9);
+    }
+
+    #[test]
+    fn circular_judgements_are_inconsistent() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut ahp = Ahp::new(&comparisons);
+        ahp.judge(&items[0], &items[1], 5);
+        ahp.judge(&items[1], &items[2], 5);
+        ahp.judge(&items[2], &items[0], 5);
+
+        let priorities = ahp.priorities();
+        assert!(!priorities.is_consistent());
+        assert!(priorities.warning().is_some());
+    }
+
+    #[test]
+    fn judgements_can_be_revised() {
+        let items: Vec<Item<usize>> = (0..2).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut ahp = Ahp::new(&comparisons);
+        assert_eq!(
+            ahp.next_comparison(),
+            Some(Comparison::new(&items[0], &items[1]))
+        );
+        ahp.judge(&items[0], &items[1], 3);
+        ahp.judge(&items[1], &items[0], 3);
+        assert!(ahp.is_complete());
+
+        let (_, matrix) = ahp.matrix();
+        assert_eq!(matrix, vec![vec![1.0, 1.0 / 3.0], vec![3.0, 1.0]]);
+        assert_eq!(ahp.priorities().ranking[0], (&items[1], 0.75));
+    }
+
+    #[test]
+    #[should_panic(expected = "an item cannot be judged against itself")]
+    fn items_cannot_be_judged_against_themselves() {
+        let items: Vec<Item<usize>> = (0..2).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        Ahp::new(&comparisons).judge(&items[0], &items[0], 3);
+    }
+}
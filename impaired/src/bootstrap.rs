@@ -0,0 +1,299 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{aggregation, Aggregation, ComparisonResult, Item};
+use std::{cmp::Ordering, collections::HashMap, hash::Hash};
+
+/// The confidence interval of an item's rank and score, as determined by a
+/// [`Bootstrap`](Bootstrap).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankInterval<'a, T> {
+    /// The item the interval belongs to.
+    pub item: &'a Item<T>,
+    /// The rank of the item according to all results, where `1` is the best rank.
+    pub rank: usize,
+    /// The best rank the item reaches within the confidence interval.
+    pub best_rank: usize,
+    /// The worst rank the item reaches within the confidence interval.
+    pub worst_rank: usize,
+    /// The score of the item according to all results.
+    pub score: f64,
+    /// The lower bound of the item's score within the confidence interval.
+    pub lower_score: f64,
+    /// The upper bound of the item's score within the confidence interval.
+    pub upper_score: f64,
+}
+
+/// Determine confidence intervals for the ranks of items by resampling the results of comparisons.
+///
+/// A single ranking overstates how certain its order is, especially if only few comparisons were
+/// answered. The bootstrap estimates the uncertainty by repeatedly drawing as many results as there
+/// are from the results with replacement, ranking the items according to every such resample, and
+/// reporting the range of ranks each item reaches in the central share of resamples given by the
+/// confidence.
+///
+/// The resampling is driven by a seeded pseudo-random number generator, so running the same
+/// bootstrap twice yields the same intervals.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Aggregation, Bootstrap, ComparisonResult, Item};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let java = Item("Java");
+///
+/// let results = [
+///     ComparisonResult::new(&rust, &cpp),
+///     ComparisonResult::new(&rust, &java),
+///     ComparisonResult::new(&java, &cpp),
+/// ];
+/// let intervals = Bootstrap::new([&rust, &cpp, &java], results)
+///     .confidence(0.9)
+///     .run(Aggregation::SumOfWins);
+///
+/// for interval in &intervals {
+///     println!(
+///         "{}: rank {} (between {} and {} with 90% confidence)",
+///         interval.item, interval.rank, interval.best_rank, interval.worst_rank,
+///     );
+/// }
+/// assert_eq!(intervals[0].item, &rust);
+/// ```
+#[derive(Debug)]
+pub struct Bootstrap<'a, T: Eq + Hash + Ord> {
+    items: Vec<&'a Item<T>>,
+    results: Vec<ComparisonResult<'a, T>>,
+    resamples: usize,
+    confidence: f64,
+    seed: u64,
+}
+
+impl<'a, T: 'a + Eq + Hash + Ord> Bootstrap<'a, T> {
+    /// Prepare a bootstrap of the ranks of the given items, based on the given results.
+    ///
+    /// By default, 1000 resamples are drawn to determine 90% confidence intervals.
+    pub fn new(
+        items: impl IntoIterator<Item = &'a Item<T>>,
+        results: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+    ) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+            results: results.into_iter().collect(),
+            resamples: 1000,
+            confidence: 0.9,
+            seed: 0x5eed,
+        }
+    }
+
+    /// Set the number of resamples to draw.
+    pub fn resamples(mut self, resamples: usize) -> Self {
+        self.resamples = resamples;
+        self
+    }
+
+    /// Set the confidence of the intervals, e.g. `0.9` for 90% confidence intervals.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the confidence is not within `0.0..=1.0`.
+    pub fn confidence(mut self, confidence: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&confidence),
+            "the confidence has to be within 0.0 and 1.0"
+        );
+        self.confidence = confidence;
+        self
+    }
+
+    /// Set the seed of the pseudo-random number generator drawing the resamples.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Run the bootstrap, ranking the items of every resample with the given aggregation method.
+    ///
+    /// The intervals are returned in the order of the ranking according to all results.
+    pub fn run(&self, aggregation: Aggregation) -> Vec<RankInterval<'a, T>> {
+        self.run_with(|results| {
+            aggregation::aggregate(aggregation, self.items.iter().copied(), results.to_vec())
+        })
+    }
+
+    /// Run the bootstrap, ranking the items of every resample with the given function.
+    ///
+    /// The function receives the results of a resample and has to return the items along with their
+    /// scores, ordered from best to worst. Consecutive items with equal scores share the best rank
+    /// among them. Items the function does not return are ranked last.
+    ///
+    /// The intervals are returned in the order of the ranking according to all results.
+    pub fn run_with<F>(&self, mut rank: F) -> Vec<RankInterval<'a, T>>
+    where
+        F: FnMut(&[ComparisonResult<'a, T>]) -> Vec<(&'a Item<T>, f64)>,
+    {
+        let full = ranks_and_scores(rank(&self.results));
+
+        let mut samples: HashMap<&'a Item<T>, (Vec<usize>, Vec<f64>)> = HashMap::new();
+        let mut rng = SplitMix64(self.seed);
+        let mut resample = Vec::with_capacity(self.results.len());
+        for _ in 0..self.resamples {
+            resample.clear();
+            resample.extend(
+                (0..self.results.len()).map(|_| self.results[rng.below(self.results.len())]),
+            );
+            let ranked = ranks_and_scores(rank(&resample));
+            for item in full.iter().map(|(item, _, _)| *item) {
+                let (rank, score) = ranked
+                    .iter()
+                    .find(|(ranked_item, _, _)| *ranked_item == item)
+                    .map(|(_, rank, score)| (*rank, Some(*score)))
+                    .unwrap_or((ranked.len() + 1, None));
+                let (ranks, scores) = samples.entry(item).or_default();
+                ranks.push(rank);
+                scores.extend(score);
+            }
+        }
+
+        let tail = (1.0 - self.confidence) / 2.0;
+        full.into_iter()
+            .map(|(item, rank, score)| {
+                let (mut ranks, mut scores) = samples.remove(item).unwrap_or_default();
+                ranks.sort_unstable();
+                scores.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                RankInterval {
+                    item,
+                    rank,
+                    best_rank: quantile(&ranks, tail).unwrap_or(rank),
+                    worst_rank: quantile(&ranks, 1.0 - tail).unwrap_or(rank),
+                    score,
+                    lower_score: quantile(&scores, tail).unwrap_or(score),
+                    upper_score: quantile(&scores, 1.0 - tail).unwrap_or(score),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Assign ranks to a ranking ordered from best to worst, where consecutive items with equal scores
+/// share the best rank among them.
+fn ranks_and_scores<T>(ranked: Vec<(&Item<T>, f64)>) -> Vec<(&Item<T>, usize, f64)> {
+    let mut result: Vec<(&Item<T>, usize, f64)> = Vec::with_capacity(ranked.len());
+    for (position, (item, score)) in ranked.into_iter().enumerate() {
+        let rank = match result.last() {
+            Some((_, previous_rank, previous_score)) if *previous_score == score => *previous_rank,
+            _ => position + 1,
+        };
+        result.push((item, rank, score));
+    }
+    result
+}
+
+/// The value below which the given share of the sorted values lies, using the nearest-rank method.
+fn quantile<V: Copy>(sorted: &[V], share: f64) -> Option<V> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let index = ((share * sorted.len() as f64).ceil() as usize).saturating_sub(1);
+    Some(sorted[index.min(sorted.len() - 1)])
+}
+
+/// A small, fast pseudo-random number generator, which is good enough to draw resamples.
+///
+/// See <https://prng.di.unimi.it/splitmix64.c>.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number within `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        // The bias of the modulo is negligible for the number of results we are dealing with.
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unanimous_results_have_narrow_intervals() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let results: Vec<_> = (0..20)
+            .flat_map(|_| {
+                [
+                    ComparisonResult::new(&items[2], &items[1]),
+                    ComparisonResult::new(&items[2], &items[0]),
+                    ComparisonResult::new(&items[1], &items[0]),
+                ]
+            })
+            .collect();
+
+        let intervals = Bootstrap::new(&items, results)
+            .resamples(200)
+            .run(Aggregation::Copeland);
+        let ranks: Vec<_> = intervals
+            .iter()
+            .map(|interval| (interval.item.0, interval.best_rank, interval.worst_rank))
+            .collect();
+        assert_eq!(ranks, vec![(2, 1, 1), (1, 2, 2), (0, 3, 3)]);
+    }
+
+    #[test]
+    fn sparse_results_have_wide_intervals() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let results = vec![
+            ComparisonResult::new(&items[3], &items[2]),
+            ComparisonResult::new(&items[2], &items[1]),
+            ComparisonResult::new(&items[1], &items[0]),
+        ];
+
+        let intervals = Bootstrap::new(&items, results).run(Aggregation::BradleyTerry);
+        assert_eq!(intervals[0].item, &items[3]);
+        assert_eq!(intervals[0].rank, 1);
+        assert!(intervals[0].worst_rank > 1);
+        assert!(intervals
+            .iter()
+            .all(|interval| interval.best_rank <= interval.rank
+                && interval.rank <= interval.worst_rank
+                && interval.lower_score <= interval.upper_score));
+    }
+
+    #[test]
+    fn bootstrap_is_deterministic_per_seed() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let results = vec![
+            ComparisonResult::new(&items[3], &items[2]),
+            ComparisonResult::new(&items[2], &items[1]),
+            ComparisonResult::new(&items[0], &items[1]),
+            ComparisonResult::new(&items[1], &items[3]),
+        ];
+        let bootstrap = Bootstrap::new(&items, results).resamples(100);
+
+        assert_eq!(
+            bootstrap.run(Aggregation::BradleyTerry),
+            bootstrap.run(Aggregation::BradleyTerry)
+        );
+    }
+
+    #[test]
+    fn tied_scores_share_the_best_rank() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let ranked = ranks_and_scores(vec![(&items[0], 2.0), (&items[1], 2.0), (&items[2], 1.0)]);
+        let ranks: Vec<_> = ranked.into_iter().map(|(_, rank, _)| rank).collect();
+        assert_eq!(ranks, vec![1, 1, 3]);
+    }
+}
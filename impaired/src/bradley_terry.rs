@@ -0,0 +1,217 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{ComparisonResult, Item};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    ops::Deref,
+};
+
+const MAX_ITERATIONS: usize = 10_000;
+const TOLERANCE: f64 = 1e-10;
+
+/// Strengths of items according to the Bradley-Terry model.
+///
+/// The Bradley-Terry model assigns every item a positive strength, such that the probability of
+/// item `a` winning against item `b` is `strength(a) / (strength(a) + strength(b))`. Contrary to
+/// simply counting wins, this takes into account against whom an item won: winning against a
+/// strong item counts for more than winning against a weak one.
+///
+/// The strengths are estimated from the results using the iterative minorization-maximization
+/// algorithm. To keep the strengths of items that won (or lost) every comparison finite, every item
+/// is credited with one virtual win and one virtual loss against a reference item of strength `1`.
+/// Items without any results thus end up with a strength of exactly `1`.
+///
+/// `BradleyTerry` dereferences into a [`HashMap`](std::collections::HashMap) mapping every
+/// [`Item`](Item) to its strength.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{BradleyTerry, ComparisonResult, Item};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let java = Item("Java");
+///
+/// let model = BradleyTerry::fit(
+///     [&rust, &cpp, &java],
+///     [
+///         ComparisonResult::new(&rust, &cpp),
+///         ComparisonResult::new(&rust, &java),
+///         ComparisonResult::new(&java, &cpp),
+///     ],
+/// );
+/// assert!(model[&rust] > model[&java]);
+/// assert!(model[&java] > model[&cpp]);
+/// assert!(model.probability(&rust, &cpp) > 0.5);
+/// ```
+#[derive(Debug)]
+pub struct BradleyTerry<'a, T>(HashMap<&'a Item<T>, f64>);
+
+impl<'a, T> BradleyTerry<'a, T>
+where
+    T: Eq + Hash + Ord,
+{
+    /// Estimate the strengths of the provided items from the results of comparisons between them.
+    ///
+    /// Items that only appear in the results, but not in `items`, are estimated as well.
+    pub fn fit(
+        items: impl IntoIterator<Item = &'a Item<T>>,
+        results: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+    ) -> Self {
+        let mut indices: HashMap<&'a Item<T>, usize> = HashMap::new();
+        let mut index_of = |item: &'a Item<T>| {
+            let next_index = indices.len();
+            *indices.entry(item).or_insert(next_index)
+        };
+        for item in items {
+            index_of(item);
+        }
+
+        // Collect the number of wins per item and the number of comparisons per pair of items.
+        let mut wins: Vec<f64> = Vec::new();
+        // The comparisons are kept ordered to make the estimation deterministic.
+        let mut comparisons: BTreeMap<(usize, usize), f64> = BTreeMap::new();
+        for result in results {
+            let winner = index_of(result.winner);
+            let loser = index_of(result.loser);
+            if wins.len() <= winner {
+                wins.resize(winner + 1, 0.0);
+            }
+            wins[winner] += 1.0;
+            *comparisons
+                .entry((winner.min(loser), winner.max(loser)))
+                .or_default() += 1.0;
+        }
+        wins.resize(indices.len(), 0.0);
+
+        let mut opponents: Vec<Vec<(usize, f64)>> = vec![Vec::new(); indices.len()];
+        for ((a, b), count) in comparisons {
+            opponents[a].push((b, count));
+            opponents[b].push((a, count));
+        }
+
+        let mut strengths = vec![1.0; indices.len()];
+        for _ in 0..MAX_ITERATIONS {
+            let mut max_change: f64 = 0.0;
+            for (item, item_opponents) in opponents.iter().enumerate() {
+                let strength = strengths[item];
+                // The virtual win and loss against the reference item of strength `1`.
+                let mut denominator = 2.0 / (strength + 1.0);
+                for (opponent, count) in item_opponents {
+                    denominator += count / (strength + strengths[*opponent]);
+                }
+                let updated = (wins[item] + 1.0) / denominator;
+                max_change = max_change.max((updated - strength).abs() / strength);
+                strengths[item] = updated;
+            }
+            if max_change < TOLERANCE {
+                break;
+            }
+        }
+
+        Self(
+            indices
+                .into_iter()
+                .map(|(item, index)| (item, strengths[index]))
+                .collect(),
+        )
+    }
+
+    /// The probability of item `a` winning a comparison against item `b` according to the model.
+    ///
+    /// Items that the model does not know are treated as having a strength of `1`.
+    pub fn probability(&self, a: &Item<T>, b: &Item<T>) -> f64 {
+        let a = self.0.get(a).copied().unwrap_or(1.0);
+        let b = self.0.get(b).copied().unwrap_or(1.0);
+        a / (a + b)
+    }
+
+    /// The items and their strengths, from strongest to weakest.
+    ///
+    /// Items with the same strength are ordered ascending.
+    pub fn ranked(&self) -> Vec<(&'a Item<T>, f64)> {
+        let mut ranked: Vec<_> = self
+            .0
+            .iter()
+            .map(|(item, strength)| (*item, *strength))
+            .collect();
+        ranked.sort_by(|(a_item, a), (b_item, b)| {
+            b.partial_cmp(a)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a_item.cmp(b_item))
+        });
+        ranked
+    }
+}
+
+impl<'a, T> Deref for BradleyTerry<'a, T> {
+    type Target = HashMap<&'a Item<T>, f64>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn items_without_results_have_unit_strength() {
+        let item1 = Item(1);
+        let item2 = Item(2);
+        let model = BradleyTerry::fit([&item1, &item2], []);
+
+        assert_eq!(model[&item1], 1.0);
+        assert_eq!(model[&item2], 1.0);
+        assert_eq!(model.probability(&item1, &item2), 0.5);
+    }
+
+    #[test]
+    fn strengths_stay_finite_for_undefeated_items() {
+        let item1 = Item(1);
+        let item2 = Item(2);
+        let model = BradleyTerry::fit(
+            [&item1, &item2],
+            [
+                ComparisonResult::new(&item1, &item2),
+                ComparisonResult::new(&item1, &item2),
+            ],
+        );
+
+        assert!(model[&item1].is_finite());
+        assert!(model[&item2] > 0.0);
+        assert!(model.probability(&item1, &item2) > 0.5);
+    }
+
+    #[test]
+    fn beating_strong_items_counts_more() {
+        // Both 1 and 2 win once, but 1 beats the undefeated-otherwise 3 while 2 beats 4, who lost
+        // everything.
+        let item1 = Item(1);
+        let item2 = Item(2);
+        let item3 = Item(3);
+        let item4 = Item(4);
+        let model = BradleyTerry::fit(
+            [&item1, &item2, &item3, &item4],
+            [
+                ComparisonResult::new(&item1, &item3),
+                ComparisonResult::new(&item3, &item4),
+                ComparisonResult::new(&item3, &item4),
+                ComparisonResult::new(&item2, &item4),
+            ],
+        );
+
+        assert!(model[&item1] > model[&item2]);
+        assert_eq!(model.ranked().last().map(|(item, _)| *item), Some(&item4));
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{aggregation, Aggregation, ComparisonResult, Comparisons, Item, Ranking};
+use std::{collections::HashMap, hash::Hash};
+
+/// Rank very large lists of items in two stages: first sort the items into coarse buckets, then
+/// compare them pairwise only within their bucket.
+///
+/// A full pairwise session requires `n * (n - 1) / 2` comparisons, which is infeasible for hundreds
+/// of items. Sorting the items into ordered buckets first, e.g. "must", "should" and "could", only
+/// requires a single, fast question per item, and reduces the pairwise comparisons to those within
+/// every bucket.
+///
+/// The buckets `B` are given from best to worst. Every item of a better bucket is ranked above every
+/// item of a worse bucket, items that were not assigned to any bucket are ranked last.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Aggregation, Bucketing, ComparisonResult, Item};
+/// let items: Vec<Item<String>> = (0..200).map(|index| Item(format!("Feature {}", index))).collect();
+///
+/// let mut bucketing = Bucketing::new(&items, ["must", "should", "could"]);
+/// while let Some(item) = bucketing.next_unassigned() {
+///     // Ask "must, should or could?" for every item.
+///     bucketing.assign(item, &"could");
+/// }
+/// bucketing.assign(&items[42], &"must");
+/// bucketing.assign(&items[7], &"must");
+///
+/// let mut results = Vec::new();
+/// let must = bucketing.comparisons(&"must").unwrap();
+/// for (comparison, result_tracker) in must.retain_item_iterator() {
+///     results.push(ComparisonResult::new(&items[42], comparison.other(&items[42])));
+///     result_tracker.winner(&items[42]);
+/// }
+///
+/// let ranking = bucketing.ranking(results, Aggregation::SumOfWins);
+/// assert_eq!(ranking[0].item, &items[42]);
+/// assert_eq!(ranking[1].item, &items[7]);
+/// assert_eq!(ranking.len(), 200);
+/// ```
+#[derive(Debug)]
+pub struct Bucketing<'a, T: Eq + Hash + Ord, B> {
+    items: Vec<&'a Item<T>>,
+    buckets: Vec<B>,
+    assignments: HashMap<&'a Item<T>, usize>,
+}
+
+impl<'a, T, B> Bucketing<'a, T, B>
+where
+    T: 'a + Eq + Hash + Ord,
+    B: PartialEq,
+{
+    /// Prepare sorting the given items into the given buckets, ordered from best to worst.
+    pub fn new(
+        items: impl IntoIterator<Item = &'a Item<T>>,
+        buckets: impl IntoIterator<Item = B>,
+    ) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+            buckets: buckets.into_iter().collect(),
+            assignments: HashMap::new(),
+        }
+    }
+
+    /// The buckets, ordered from best to worst.
+    pub fn buckets(&self) -> &[B] {
+        &self.buckets
+    }
+
+    /// Assign an item to a bucket, replacing any previous assignment.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the bucket is unknown.
+    pub fn assign(&mut self, item: &'a Item<T>, bucket: &B) {
+        let bucket = self
+            .buckets
+            .iter()
+            .position(|known| known == bucket)
+            .expect("the bucket is unknown");
+        self.assignments.insert(item, bucket);
+    }
+
+    /// The bucket an item is assigned to, if any.
+    pub fn bucket_of(&self, item: &Item<T>) -> Option<&B> {
+        self.assignments
+            .get(item)
+            .map(|bucket| &self.buckets[*bucket])
+    }
+
+    /// The next item, in the order the items were given, that is not assigned to a bucket yet.
+    pub fn next_unassigned(&self) -> Option<&'a Item<T>> {
+        self.items
+            .iter()
+            .find(|item| !self.assignments.contains_key(*item))
+            .copied()
+    }
+
+    /// All items assigned to a bucket, in the order the items were given.
+    pub fn items(&self, bucket: &B) -> Vec<&'a Item<T>> {
+        self.items
+            .iter()
+            .filter(|item| self.bucket_of(item) == Some(bucket))
+            .copied()
+            .collect()
+    }
+
+    /// The comparisons between the items of a bucket, or `None` if the bucket contains less than
+    /// two items, such that there is nothing to compare.
+    pub fn comparisons(&self, bucket: &B) -> Option<Comparisons<'a, T>> {
+        let items = self.items(bucket);
+        if items.len() < 2 {
+            None
+        } else {
+            Some(Comparisons::new(items))
+        }
+    }
+
+    /// Merge the buckets and the results of the comparisons within them into a single ranking,
+    /// ordered from best to worst.
+    ///
+    /// The items of every bucket are ranked by the given aggregation method, which only considers
+    /// results between items of the same bucket. The scores are therefore only comparable within a
+    /// bucket, and items are only tied with items of the same bucket. Unassigned items get the
+    /// worst score possible for the aggregation, e.g. negative infinity for
+    /// [`SumOfWins`](Aggregation::SumOfWins), and are tied with each other.
+    pub fn ranking(
+        &self,
+        results: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+        aggregation: Aggregation,
+    ) -> Ranking<'a, T> {
+        let mut within_buckets = Vec::new();
+        let mut results_by_bucket: HashMap<usize, Vec<ComparisonResult<'a, T>>> = HashMap::new();
+        for result in results {
+            match (
+                self.assignments.get(result.winner),
+                self.assignments.get(result.loser),
+            ) {
+                (Some(winner), Some(loser)) if winner == loser => {
+                    within_buckets.push(result);
+                    results_by_bucket.entry(*winner).or_default().push(result)
+                }
+                _ => {}
+            }
+        }
+
+        let mut ranking = Vec::with_capacity(self.items.len());
+        for bucket in 0..self.buckets.len() {
+            let items = self
+                .items
+                .iter()
+                .filter(|item| self.assignments.get(*item) == Some(&bucket))
+                .copied();
+            let results = results_by_bucket.remove(&bucket).unwrap_or_default();
+            ranking.extend(aggregation::aggregate(aggregation, items, results));
+        }
+
+        ranking.extend(
+            self.items
+                .iter()
+                .filter(|item| !self.assignments.contains_key(*item))
+                .map(|item| (*item, aggregation.worst_score())),
+        );
+
+        Ranking::with_ties_if(ranking, |a, b| {
+            self.assignments.get(a) == self.assignments.get(b)
+        })
+        .break_ties(within_buckets)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn buckets_are_ranked_in_order() {
+        let items: Vec<Item<usize>> = (0..6).map(Item).collect();
+        let mut bucketing = Bucketing::new(&items, ["high", "low"]);
+        for item in &items[..4] {
+            bucketing.assign(item, if item.0 % 2 == 0 { &"low" } else { &"high" });
+        }
+
+        let results = vec![
+            ComparisonResult::new(&items[3], &items[1]),
+            ComparisonResult::new(&items[0], &items[2]),
+            // Results across buckets are ignored.
+            ComparisonResult::new(&items[0], &items[1]),
+        ];
+        let ranking: Vec<_> = bucketing
+            .ranking(results, Aggregation::SumOfWins)
+            .iter()
+            .map(|ranked| (ranked.item.0, ranked.score, ranked.rank))
+            .collect();
+        assert_eq!(
+            ranking,
+            vec![
+                (3, 1.0, 1),
+                (1, 0.0, 2),
+                (0, 1.0, 3),
+                (2, 0.0, 4),
+                (4, f64::NEG_INFINITY, 5),
+                (5, f64::NEG_INFINITY, 5)
+            ]
+        );
+
+        // Equal scores do not tie items across buckets, unassigned items rank last under mean ranks
+        // as well.
+        let ranking = bucketing.ranking(Vec::new(), Aggregation::MeanRank);
+        let ranks: Vec<_> = ranking.iter().map(|ranked| ranked.rank).collect();
+        assert_eq!(ranks, vec![1, 1, 3, 3, 5, 5]);
+        assert_eq!(ranking[4].score, f64::INFINITY);
+    }
+
+    #[test]
+    fn only_comparisons_within_a_bucket_are_asked() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let mut bucketing = Bucketing::new(&items, [1, 2, 3]);
+        bucketing.assign(&items[0], &1);
+        bucketing.assign(&items[1], &1);
+        bucketing.assign(&items[2], &1);
+        bucketing.assign(&items[3], &2);
+        bucketing.assign(&items[4], &1);
+        bucketing.assign(&items[4], &2);
+
+        assert_eq!(bucketing.next_unassigned(), None);
+        assert_eq!(bucketing.bucket_of(&items[4]), Some(&2));
+        assert_eq!(bucketing.comparisons(&1).unwrap().len(), 3);
+        assert_eq!(bucketing.comparisons(&2).unwrap().len(), 1);
+        assert!(bucketing.comparisons(&3).is_none());
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{Comparison, ComparisonResult, ComparisonResultTracker, Comparisons, Item, Scores};
+use std::{
+    cell::RefCell,
+    cmp::{self, Ordering},
+    collections::HashMap,
+    hash::Hash,
+    rc::Rc,
+};
+
+/// An iterator asking at most a fixed number of comparisons, choosing them such that the resulting
+/// ranking is as informative as possible within that budget.
+///
+/// Instead of walking the comparisons in an arbitrary order and stopping once the budget is used up,
+/// this iterator pairs items similar to a Swiss-system tournament:
+///
+/// 1. Items that have been compared the least are preferred, such that the budget is spread evenly
+///    across all items and every item gets compared as early as possible.
+/// 2. Among those, items with a similar share of wins are paired up, because comparisons between
+///    items of similar strength tell us more about their order than lopsided ones.
+///
+/// The second rule can only take effect if you track the winners, either through the
+/// [`ComparisonResultTracker`](ComparisonResultTracker) or
+/// [`BudgetIterator::winner`](BudgetIterator::winner). Every comparison is yielded at most once.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Comparisons, Item};
+/// let items: Vec<Item<usize>> = (0..20).map(Item).collect();
+/// let comparisons = Comparisons::new(items.iter());
+///
+/// let mut asked = 0;
+/// for (comparison, result_tracker) in comparisons.budget_iterator(40) {
+///     // The bigger number always wins.
+///     result_tracker.winner(std::cmp::max(comparison.left, comparison.right));
+///     asked += 1;
+/// }
+/// assert_eq!(asked, 40);
+/// ```
+pub struct BudgetIterator<'a, T: Eq + Hash + Ord> {
+    remaining: Comparisons<'a, T>,
+    budget: usize,
+    standings: Standings<'a, T>,
+    previous_comparison: Option<Comparison<'a, T>>,
+    previous_comparison_recorded: bool,
+    previous_comparison_result: Rc<RefCell<Option<ComparisonResult<'a, T>>>>,
+}
+
+impl<'a, T: Eq + Hash + Ord> BudgetIterator<'a, T> {
+    pub(crate) fn new(input: &Comparisons<'a, T>, budget: usize) -> Self {
+        Self {
+            remaining: input.clone(),
+            budget,
+            standings: Standings::default(),
+            previous_comparison: None,
+            previous_comparison_recorded: false,
+            previous_comparison_result: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// The number of comparisons this iterator will yield at most from now on.
+    pub fn remaining_budget(&self) -> usize {
+        cmp::min(self.budget, self.remaining.len())
+    }
+
+    /// Track the winner of the current comparison.
+    ///
+    /// This fulfills the same purpose as
+    /// [`ComparisonResultTracker::winner`](ComparisonResultTracker::winner), see
+    /// [`RetainItemIterator::winner`](crate::RetainItemIterator::winner) for when to use which.
+    pub fn winner(&mut self, winner: &'a Item<T>) {
+        if let Some(previous_comparison) = self.previous_comparison {
+            let loser = previous_comparison.other(winner);
+            self.previous_comparison_result
+                .borrow_mut()
+                .replace(ComparisonResult {
+                    comparison: previous_comparison,
+                    winner,
+                    loser,
+                });
+        }
+    }
+
+    fn record_previous_comparison_result(&mut self) {
+        if self.previous_comparison_recorded {
+            return;
+        }
+        self.previous_comparison_recorded = true;
+
+        if let (Some(previous_comparison), Some(previous_comparison_result)) = (
+            self.previous_comparison,
+            *self.previous_comparison_result.borrow(),
+        ) {
+            // The result might still be the one of an earlier comparison if the winner of the
+            // previous comparison wasn't tracked.
+            if previous_comparison_result.comparison == previous_comparison {
+                self.standings.scores.track(
+                    previous_comparison_result.winner,
+                    previous_comparison_result.loser,
+                );
+            }
+        }
+    }
+}
+
+/// How often every item was compared and won, to pair items similar to a Swiss-system tournament.
+pub(crate) struct Standings<'a, T> {
+    compared: HashMap<&'a Item<T>, usize>,
+    pub(crate) scores: Scores<'a, T>,
+}
+
+impl<'a, T> Default for Standings<'a, T> {
+    fn default() -> Self {
+        Self {
+            compared: HashMap::new(),
+            scores: Scores::default(),
+        }
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Standings<'a, T> {
+    /// Record that a comparison was asked, regardless of its result.
+    pub(crate) fn compared(&mut self, comparison: &Comparison<'a, T>) {
+        *self.compared.entry(comparison.left).or_default() += 1;
+        *self.compared.entry(comparison.right).or_default() += 1;
+    }
+
+    /// Order comparisons by how informative they are, most informative first.
+    ///
+    /// Comparisons between items that have been compared the least come first, and among those,
+    /// comparisons between items with a similar share of wins.
+    pub(crate) fn cmp_priority(&self, a: &Comparison<'a, T>, b: &Comparison<'a, T>) -> Ordering {
+        self.priority(a)
+            .partial_cmp(&self.priority(b))
+            .unwrap_or(Ordering::Equal)
+            // Break remaining ties deterministically instead of relying on the iteration order.
+            .then_with(|| {
+                (cmp::min(a.left, a.right), cmp::max(a.left, a.right))
+                    .cmp(&(cmp::min(b.left, b.right), cmp::max(b.left, b.right)))
+            })
+    }
+
+    fn times_compared(&self, item: &'a Item<T>) -> usize {
+        self.compared.get(item).copied().unwrap_or_default()
+    }
+
+    fn share_of_wins(&self, item: &'a Item<T>) -> f64 {
+        let wins = self.scores.get(item).copied().unwrap_or_default();
+        match self.times_compared(item) {
+            0 => 0.5,
+            compared => wins as f64 / compared as f64,
+        }
+    }
+
+    fn priority(&self, comparison: &Comparison<'a, T>) -> (usize, usize, f64) {
+        let left = self.times_compared(comparison.left);
+        let right = self.times_compared(comparison.right);
+        (
+            cmp::max(left, right),
+            left + right,
+            (self.share_of_wins(comparison.left) - self.share_of_wins(comparison.right)).abs(),
+        )
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Iterator for BudgetIterator<'a, T> {
+    type Item = (Comparison<'a, T>, ComparisonResultTracker<'a, T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.record_previous_comparison_result();
+        if self.budget == 0 {
+            return None;
+        }
+
+        let comparison = self
+            .remaining
+            .iter()
+            .min_by(|a, b| self.standings.cmp_priority(a, b))?;
+
+        self.remaining.remove(&comparison);
+        self.budget -= 1;
+        self.standings.compared(&comparison);
+        self.previous_comparison.replace(comparison);
+        self.previous_comparison_recorded = false;
+
+        Some((
+            comparison,
+            ComparisonResultTracker {
+                comparison,
+                comparison_result: self.previous_comparison_result.clone(),
+            },
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining_budget();
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn budget_is_respected() {
+        let items: Vec<Item<usize>> = (0..10).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let mut seen = HashSet::new();
+        for (comparison, result_tracker) in comparisons.budget_iterator(12) {
+            assert!(seen.insert(comparison));
+            result_tracker.winner(comparison.left);
+        }
+        assert_eq!(seen.len(), 12);
+    }
+
+    #[test]
+    fn budget_larger_than_comparisons_yields_every_comparison() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        assert_eq!(comparisons.budget_iterator(100).count(), comparisons.len());
+    }
+
+    #[test]
+    fn budget_is_spread_across_items() {
+        let items: Vec<Item<usize>> = (0..10).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let mut compared: HashMap<&Item<usize>, usize> = HashMap::new();
+        for (comparison, result_tracker) in comparisons.budget_iterator(15) {
+            *compared.entry(comparison.left).or_default() += 1;
+            *compared.entry(comparison.right).or_default() += 1;
+            result_tracker.winner(cmp::max(comparison.left, comparison.right));
+        }
+
+        assert_eq!(compared.len(), items.len());
+        assert!(compared.values().all(|compared| *compared == 3));
+    }
+
+    #[test]
+    fn similar_items_are_paired() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut iterator = comparisons.budget_iterator(4);
+
+        // The first round pairs every item once.
+        let mut winners = HashSet::new();
+        for _ in 0..2 {
+            let (comparison, _) = iterator.next().unwrap();
+            let winner = cmp::max(comparison.left, comparison.right);
+            winners.insert(winner);
+            iterator.winner(winner);
+        }
+
+        // The second round has to pair the two winners and the two losers respectively.
+        for _ in 0..2 {
+            let (comparison, _) = iterator.next().unwrap();
+            assert_eq!(
+                winners.contains(comparison.left),
+                winners.contains(comparison.right)
+            );
+            iterator.winner(cmp::max(comparison.left, comparison.right));
+        }
+        assert!(iterator.next().is_none());
+    }
+}
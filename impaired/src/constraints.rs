@@ -0,0 +1,373 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{ComparisonResult, Comparisons, Item, Ranking, WarmStart};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    hash::Hash,
+};
+
+/// For every item, the items that have to be ranked below it.
+type Relation<'a, T> = HashMap<&'a Item<T>, HashSet<&'a Item<T>>>;
+
+/// Hard constraints on the final order of items, e.g. because of regulatory requirements.
+///
+/// Constraints are treated as fixed results: the comparisons they decide, including those that
+/// follow transitively, are never asked (see [`Constraints::apply`](Constraints::apply)), and the
+/// final ranking is reordered to satisfy them (see [`Constraints::enforce`](Constraints::enforce)).
+/// A [`Session`](crate::Session) started through
+/// [`Session::warm_start`](crate::Session::warm_start) takes the decided comparisons into account.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Comparisons, Constraints, Item, Outcome, Session};
+/// let gdpr = Item("GDPR compliance");
+/// let dark_mode = Item("Dark mode");
+/// let search = Item("Search");
+/// let export = Item("Export");
+///
+/// let constraints = Constraints::new()
+///     .pin_top(&gdpr)
+///     .above(&search, &export);
+///
+/// let comparisons = Comparisons::new([&gdpr, &dark_mode, &search, &export]);
+/// let constrained = constraints.apply(&comparisons).expect("consistent constraints");
+/// // GDPR beats everything, and search beats export.
+/// assert_eq!(constrained.established.len(), 4);
+/// assert_eq!(constrained.comparisons.len(), 2);
+///
+/// let mut session = Session::warm_start(&constrained);
+/// while let Some(comparison) = session.current_question() {
+///     // Dark mode wins everything that is still asked.
+///     session.answer(if comparison.left == &dark_mode {
+///         Outcome::Left
+///     } else {
+///         Outcome::Right
+///     })?;
+/// }
+///
+/// let ranking = constraints
+///     .enforce(session.ranking())
+///     .expect("consistent constraints");
+/// let order: Vec<_> = ranking.iter().map(|ranked| ranked.item).collect();
+/// assert_eq!(order, vec![&gdpr, &dark_mode, &search, &export]);
+/// # Ok::<(), impaired::SessionError>(())
+/// ```
+#[derive(Debug)]
+pub struct Constraints<'a, T> {
+    above: Vec<(&'a Item<T>, &'a Item<T>)>,
+    pinned_top: Vec<&'a Item<T>>,
+    pinned_bottom: Vec<&'a Item<T>>,
+}
+
+impl<'a, T> Default for Constraints<'a, T> {
+    fn default() -> Self {
+        Self {
+            above: Vec::new(),
+            pinned_top: Vec::new(),
+            pinned_bottom: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Constraints<'a, T> {
+    /// Create an empty set of constraints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require `higher` to be ranked above `lower`.
+    pub fn above(mut self, higher: &'a Item<T>, lower: &'a Item<T>) -> Self {
+        self.above.push((higher, lower));
+        self
+    }
+
+    /// Require `item` to be ranked above all other items.
+    ///
+    /// If multiple items are pinned to the top, the item pinned first is ranked first.
+    pub fn pin_top(mut self, item: &'a Item<T>) -> Self {
+        self.pinned_top.push(item);
+        self
+    }
+
+    /// Require `item` to be ranked below all other items.
+    ///
+    /// If multiple items are pinned to the bottom, the item pinned first is ranked last.
+    pub fn pin_bottom(mut self, item: &'a Item<T>) -> Self {
+        self.pinned_bottom.push(item);
+        self
+    }
+
+    /// Determine which comparisons the constraints decide, and which remain to be asked.
+    ///
+    /// Comparisons that follow transitively from the constraints are decided as well, e.g. if `a`
+    /// has to be above `b` and `b` above `c`, the comparison of `a` and `c` is not asked. Track the
+    /// [established results](WarmStart::established) like any other result.
+    ///
+    /// Returns an error if the constraints contradict each other.
+    pub fn apply(
+        &self,
+        comparisons: &Comparisons<'a, T>,
+    ) -> Result<WarmStart<'a, T>, ConstraintError<'a, T>> {
+        let below = self.closure(&comparisons.items())?;
+
+        let mut established = Vec::new();
+        let mut remaining = comparisons.clone();
+        remaining.retain(|comparison| {
+            if below[comparison.left].contains(comparison.right) {
+                established.push(ComparisonResult::new(comparison.left, comparison.right));
+            } else if below[comparison.right].contains(comparison.left) {
+                established.push(ComparisonResult::new(comparison.right, comparison.left));
+            } else {
+                return true;
+            }
+            false
+        });
+        // Keep the established results in a deterministic order.
+        established.sort_by(|a, b| (a.winner, a.loser).cmp(&(b.winner, b.loser)));
+
+        Ok(WarmStart {
+            comparisons: remaining,
+            established,
+        })
+    }
+
+    /// Reorder a ranking, ordered from best to worst, such that it satisfies the constraints.
+    ///
+    /// The ranking is changed as little as possible: every item is placed as high as its position
+    /// in the original ranking allows, as long as all items it has to be ranked below are placed
+    /// before it. The scores are kept with their items, such that they might not be descending
+    /// anymore afterwards. Items stay tied if they were tied before and no constraint orders them.
+    ///
+    /// Returns an error if the constraints contradict each other.
+    pub fn enforce(
+        &self,
+        ranking: Ranking<'a, T>,
+    ) -> Result<Ranking<'a, T>, ConstraintError<'a, T>> {
+        let items: Vec<_> = ranking.iter().map(|ranked| ranked.item).collect();
+        let below = self.closure(&items)?;
+
+        let ranks: HashMap<&'a Item<T>, usize> = ranking
+            .iter()
+            .map(|ranked| (ranked.item, ranked.rank))
+            .collect();
+        let mut remaining: Vec<Option<(&'a Item<T>, f64)>> = ranking
+            .iter()
+            .map(|ranked| Some((ranked.item, ranked.score)))
+            .collect();
+        let mut enforced = Vec::with_capacity(remaining.len());
+        while enforced.len() < remaining.len() {
+            let unplaced: Vec<&'a Item<T>> =
+                remaining.iter().flatten().map(|(item, _)| *item).collect();
+            // The closure is acyclic, so there is always an item no unplaced item has to be above.
+            let next = remaining
+                .iter()
+                .position(|entry| match entry {
+                    Some((item, _)) => !unplaced.iter().any(|other| below[other].contains(item)),
+                    None => false,
+                })
+                .expect("acyclic constraints always leave an item to place");
+            enforced.extend(remaining[next].take());
+        }
+        Ok(Ranking::with_ties_if(enforced, |a, b| {
+            ranks[a] == ranks[b] && !below[a].contains(b) && !below[b].contains(a)
+        }))
+    }
+
+    /// Determine for every item which items have to be ranked below it, directly or transitively.
+    fn closure(&self, items: &[&'a Item<T>]) -> Result<Relation<'a, T>, ConstraintError<'a, T>> {
+        let mut edges: Relation<'a, T> = items.iter().map(|item| (*item, HashSet::new())).collect();
+        let mut add_edge = |higher: &'a Item<T>, lower: &'a Item<T>| {
+            edges.entry(lower).or_default();
+            edges.entry(higher).or_default().insert(lower);
+        };
+
+        for (higher, lower) in &self.above {
+            add_edge(higher, lower);
+        }
+        for (position, pinned) in self.pinned_top.iter().enumerate() {
+            for item in items {
+                if !self.pinned_top[..=position].contains(item) {
+                    add_edge(pinned, item);
+                }
+            }
+        }
+        for (position, pinned) in self.pinned_bottom.iter().enumerate() {
+            for item in items {
+                if !self.pinned_bottom[..=position].contains(item) {
+                    add_edge(item, pinned);
+                }
+            }
+        }
+
+        // Visit the items in order, such that the same contradiction is reported every time.
+        let mut items: Vec<_> = edges.keys().copied().collect();
+        items.sort_unstable();
+        let mut closure = HashMap::with_capacity(edges.len());
+        for item in items {
+            let below = reachable(&edges, item);
+            if below.contains(item) {
+                // Report a constraint on the cycle: one whose lower item leads back to `item`.
+                let mut lower: Vec<_> = edges[item].iter().copied().collect();
+                lower.sort_unstable();
+                let lower = lower
+                    .into_iter()
+                    .find(|lower| *lower == item || reachable(&edges, lower).contains(item))
+                    .expect("an item on a cycle has a successor on the cycle");
+                return Err(ConstraintError {
+                    higher: item,
+                    lower,
+                });
+            }
+            closure.insert(item, below);
+        }
+        Ok(closure)
+    }
+}
+
+/// All items that can be reached from `item` by following the edges, not including `item` itself
+/// unless it is part of a cycle.
+fn reachable<'a, T: Eq + Hash>(edges: &Relation<'a, T>, item: &'a Item<T>) -> HashSet<&'a Item<T>> {
+    let mut reached = HashSet::new();
+    let mut stack: Vec<_> = edges[item].iter().copied().collect();
+    while let Some(lower) = stack.pop() {
+        if reached.insert(lower) {
+            stack.extend(edges[lower].iter().copied());
+        }
+    }
+    reached
+}
+
+/// The error returned if [`Constraints`](Constraints) contradict each other.
+///
+/// The constraints require `higher` to be ranked above `lower`, but (transitively) also `lower` to
+/// be ranked above `higher`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConstraintError<'a, T> {
+    /// The item that has to be ranked above `lower`.
+    pub higher: &'a Item<T>,
+    /// The item that has to be ranked below `higher`, but ends up above it.
+    pub lower: &'a Item<T>,
+}
+
+impl<'a, T: Display> Display for ConstraintError<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "contradicting constraints: '{}' has to be ranked both above and below '{}'",
+            self.higher, self.lower
+        )
+    }
+}
+
+impl<'a, T: Debug + Display> Error for ConstraintError<'a, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn transitive_constraints_are_established() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let constraints = Constraints::new()
+            .above(&items[0], &items[1])
+            .above(&items[1], &items[2]);
+
+        let constrained = constraints.apply(&comparisons).unwrap();
+        assert_eq!(
+            constrained.established,
+            vec![
+                ComparisonResult::new(&items[0], &items[1]),
+                ComparisonResult::new(&items[0], &items[2]),
+                ComparisonResult::new(&items[1], &items[2]),
+            ]
+        );
+        assert_eq!(constrained.comparisons.len(), 3);
+    }
+
+    #[test]
+    fn pinned_items_keep_their_order() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let constraints = Constraints::new()
+            .pin_top(&items[3])
+            .pin_top(&items[4])
+            .pin_bottom(&items[0]);
+
+        // All items are tied, only the constraints order them.
+        let ranking = Ranking::new(items.iter().map(|item| (item, 0.0)));
+        let enforced: Vec<_> = constraints
+            .enforce(ranking)
+            .unwrap()
+            .iter()
+            .map(|ranked| (ranked.item.0, ranked.rank))
+            .collect();
+        assert_eq!(enforced, vec![(3, 1), (4, 2), (1, 3), (2, 3), (0, 5)]);
+    }
+
+    #[test]
+    fn enforcing_moves_items_as_little_as_possible() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let constraints = Constraints::new().above(&items[3], &items[1]);
+
+        let ranking = Ranking::new([
+            (&items[0], 5.0),
+            (&items[1], 4.0),
+            (&items[2], 3.0),
+            (&items[3], 2.0),
+            (&items[4], 1.0),
+        ]);
+        let enforced: Vec<_> = constraints
+            .enforce(ranking)
+            .unwrap()
+            .iter()
+            .map(|ranked| (ranked.item.0, ranked.score, ranked.rank))
+            .collect();
+        assert_eq!(
+            enforced,
+            vec![
+                (0, 5.0, 1),
+                (2, 3.0, 2),
+                (3, 2.0, 3),
+                (1, 4.0, 4),
+                (4, 1.0, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn contradictions_are_reported() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let constraints = Constraints::new()
+            .above(&items[0], &items[1])
+            .above(&items[1], &items[2])
+            .pin_top(&items[2]);
+
+        // The cycle is 0 > 1 > 2 > 0, pinning 2 to the top also puts it above 1.
+        let error = constraints.apply(&comparisons).unwrap_err();
+        assert_eq!(error.higher, &items[0]);
+        assert_eq!(error.lower, &items[1]);
+
+        // 0 is above 1 as well, which is not part of the cycle 0 > 2 > 0.
+        let constraints = Constraints::new()
+            .above(&items[0], &items[1])
+            .above(&items[0], &items[2])
+            .above(&items[2], &items[0]);
+        let error = constraints.apply(&comparisons).unwrap_err();
+        assert_eq!(error.higher, &items[0]);
+        assert_eq!(error.lower, &items[2]);
+        assert!(constraints
+            .enforce(Ranking::new(items.iter().map(|item| (item, 0.0))))
+            .is_err());
+    }
+}
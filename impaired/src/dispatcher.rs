@@ -0,0 +1,234 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{budget::Standings, Comparison, ComparisonResult, Comparisons, Item, Scores};
+use std::{collections::HashSet, hash::Hash};
+
+/// Hand out batches of comparisons to be answered concurrently, accepting their results in any
+/// order.
+///
+/// Iterators like the [`RetainItemIterator`](crate::RetainItemIterator) decide on the next
+/// comparison based on the result of the previous one, which requires every comparison to be
+/// answered before the next can be asked. The dispatcher instead hands out batches of pending
+/// comparisons, e.g. one per voter, and records results whenever they arrive.
+///
+/// Within a batch, and across all comparisons handed out but not answered yet, comparisons are
+/// independent: no item is part of more than one of them. Comparisons sharing items are only handed
+/// out once there are not enough independent ones left. Among the candidates, the most informative
+/// comparisons are chosen the same way as by the [`BudgetIterator`](crate::BudgetIterator).
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Comparisons, Dispatcher, Item};
+/// let items: Vec<Item<usize>> = (0..6).map(Item).collect();
+/// let comparisons = Comparisons::new(items.iter());
+/// let mut dispatcher = Dispatcher::new(&comparisons);
+///
+/// while !dispatcher.is_finished() {
+///     // Three voters answer a comparison each, in whatever order they finish.
+///     let batch = dispatcher.request_batch(3);
+///     for comparison in batch.iter().rev() {
+///         let winner = std::cmp::max(comparison.left, comparison.right);
+///         assert!(dispatcher.submit(comparison, winner));
+///     }
+/// }
+///
+/// assert_eq!(dispatcher.results().len(), comparisons.len());
+/// assert_eq!(dispatcher.scores()[&items[5]], 5);
+/// ```
+pub struct Dispatcher<'a, T: Eq + Hash + Ord> {
+    pending: Comparisons<'a, T>,
+    outstanding: Vec<Comparison<'a, T>>,
+    results: Vec<ComparisonResult<'a, T>>,
+    standings: Standings<'a, T>,
+}
+
+impl<'a, T: Eq + Hash + Ord> Dispatcher<'a, T> {
+    /// Prepare dispatching the given comparisons.
+    pub fn new(comparisons: &Comparisons<'a, T>) -> Self {
+        Self {
+            pending: comparisons.clone(),
+            outstanding: Vec::new(),
+            results: Vec::new(),
+            standings: Standings::default(),
+        }
+    }
+
+    /// Hand out up to `size` comparisons that have neither been answered nor handed out yet.
+    ///
+    /// The comparisons are considered outstanding until their result is
+    /// [submitted](Dispatcher::submit) or they are [released](Dispatcher::release). Fewer
+    /// comparisons are returned if there are not enough pending comparisons left.
+    pub fn request_batch(&mut self, size: usize) -> Vec<Comparison<'a, T>> {
+        let mut candidates: Vec<_> = self.pending.iter().collect();
+        candidates.sort_by(|a, b| self.standings.cmp_priority(a, b));
+
+        let mut busy: HashSet<&'a Item<T>> = self
+            .outstanding
+            .iter()
+            .flat_map(|comparison| [comparison.left, comparison.right])
+            .collect();
+        let mut batch = Vec::with_capacity(size);
+        for comparison in &candidates {
+            if batch.len() == size {
+                break;
+            }
+            if !busy.contains(comparison.left) && !busy.contains(comparison.right) {
+                busy.insert(comparison.left);
+                busy.insert(comparison.right);
+                batch.push(*comparison);
+            }
+        }
+        // Keep every voter busy towards the end of a session, even if that means handing out
+        // comparisons that share items.
+        for comparison in candidates {
+            if batch.len() == size {
+                break;
+            }
+            if !batch.contains(&comparison) {
+                batch.push(comparison);
+            }
+        }
+
+        for comparison in &batch {
+            self.pending.remove(comparison);
+        }
+        self.outstanding.extend(&batch);
+        batch
+    }
+
+    /// Submit the winner of an outstanding comparison.
+    ///
+    /// Returns `false`, ignoring the result, if the comparison is not outstanding, e.g. because its
+    /// result has already been submitted or it has been released, or if the winner is not part of
+    /// the comparison. The comparison stays outstanding in the latter case.
+    pub fn submit(&mut self, comparison: &Comparison<'a, T>, winner: &'a Item<T>) -> bool {
+        if comparison.left != winner && comparison.right != winner {
+            return false;
+        }
+        if !self.take_outstanding(comparison) {
+            return false;
+        }
+        let result = ComparisonResult::new(winner, comparison.other(winner));
+        self.standings.compared(comparison);
+        self.standings.scores.track(result.winner, result.loser);
+        self.results.push(result);
+        true
+    }
+
+    /// Return an outstanding comparison without a result, e.g. because the voter it was handed out
+    /// to left, such that it can be handed out again.
+    ///
+    /// Returns `false` if the comparison is not outstanding.
+    pub fn release(&mut self, comparison: &Comparison<'a, T>) -> bool {
+        if !self.take_outstanding(comparison) {
+            return false;
+        }
+        self.pending.insert(comparison);
+        true
+    }
+
+    /// The comparisons that have been handed out, but not answered or released yet, in the order
+    /// they were handed out.
+    pub fn outstanding(&self) -> &[Comparison<'a, T>] {
+        &self.outstanding
+    }
+
+    /// The number of comparisons that have not been handed out yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// All results submitted so far, in the order they were submitted.
+    pub fn results(&self) -> &[ComparisonResult<'a, T>] {
+        &self.results
+    }
+
+    /// The scores according to the results submitted so far.
+    pub fn scores(&self) -> &Scores<'a, T> {
+        &self.standings.scores
+    }
+
+    /// Whether every comparison has been answered.
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty() && self.outstanding.is_empty()
+    }
+
+    fn take_outstanding(&mut self, comparison: &Comparison<'a, T>) -> bool {
+        match self
+            .outstanding
+            .iter()
+            .position(|outstanding| outstanding == comparison)
+        {
+            Some(position) => {
+                self.outstanding.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn batches_are_independent_while_possible() {
+        let items: Vec<Item<usize>> = (0..6).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut dispatcher = Dispatcher::new(&comparisons);
+
+        let first = dispatcher.request_batch(2);
+        let second = dispatcher.request_batch(2);
+        assert_eq!(second.len(), 2);
+        // The first three comparisons cover every item exactly once, only the last one overlaps.
+        let independent: HashSet<_> = first
+            .iter()
+            .chain(&second[..1])
+            .flat_map(|comparison| [comparison.left, comparison.right])
+            .collect();
+        assert_eq!(independent.len(), items.len());
+        assert_eq!(dispatcher.outstanding().len(), 4);
+    }
+
+    #[test]
+    fn results_are_accepted_in_any_order_once() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut dispatcher = Dispatcher::new(&comparisons);
+
+        let batch = dispatcher.request_batch(2);
+        assert!(dispatcher.submit(&batch[1], batch[1].left));
+        assert!(!dispatcher.submit(&batch[1], batch[1].left));
+        // Both comparisons of the batch are independent, so they share no item.
+        assert!(!dispatcher.submit(&batch[0], batch[1].left));
+        assert!(dispatcher.submit(&batch[0], batch[0].right));
+        assert_eq!(dispatcher.results().len(), 2);
+        assert_eq!(dispatcher.pending(), 4);
+    }
+
+    #[test]
+    fn released_comparisons_are_handed_out_again() {
+        let items: Vec<Item<usize>> = (0..2).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut dispatcher = Dispatcher::new(&comparisons);
+
+        let batch = dispatcher.request_batch(5);
+        assert_eq!(batch.len(), 1);
+        assert!(dispatcher.request_batch(1).is_empty());
+        assert!(dispatcher.release(&batch[0]));
+        assert!(!dispatcher.release(&batch[0]));
+        assert!(!dispatcher.is_finished());
+        assert_eq!(dispatcher.request_batch(1), batch);
+        assert!(dispatcher.submit(&batch[0], &items[0]));
+        assert!(dispatcher.is_finished());
+    }
+}
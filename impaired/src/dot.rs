@@ -0,0 +1,205 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::HeadToHead;
+use std::{
+    fmt::Display,
+    hash::Hash,
+    io::{self, Write},
+};
+
+impl<'a, T: Display + Eq + Hash + Ord> HeadToHead<'a, T> {
+    /// Write the preference graph in the [DOT language](https://graphviz.org/doc/info/lang.html)
+    /// of Graphviz.
+    ///
+    /// Every item is a node, and an edge from one item to another means that the item beat the
+    /// other one more often than it lost against it. Edges are labelled with the number of wins and
+    /// losses between the two items, e.g. `3:1`, and items that beat each other equally often are
+    /// not connected.
+    ///
+    /// The graph is transitively reduced to keep it readable: if `a` beats `b` and `b` beats `c`,
+    /// the edge from `a` to `c` is left out, as its direction is implied. Items whose preferences
+    /// form a cycle, e.g. `a` beats `b`, `b` beats `c` and `c` beats `a`, are highlighted along with
+    /// the edges of the cycle, which are never left out.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use impaired::{ComparisonResult, HeadToHead, Item};
+    /// let rust = Item("Rust");
+    /// let cpp = Item("C++");
+    /// let java = Item("Java");
+    ///
+    /// let mut head_to_head = HeadToHead::new([&rust, &cpp, &java]);
+    /// for result in [
+    ///     ComparisonResult::new(&rust, &cpp),
+    ///     ComparisonResult::new(&rust, &java),
+    ///     ComparisonResult::new(&cpp, &java),
+    /// ] {
+    ///     head_to_head.track(&result.into());
+    /// }
+    ///
+    /// let mut dot = Vec::new();
+    /// head_to_head.write_dot(&mut dot)?;
+    /// let dot = String::from_utf8(dot).unwrap();
+    /// assert!(dot.contains("n0 -> n1 [label=\"1:0\"];"));
+    /// // Rust beating Java is implied by Rust beating C++ and C++ beating Java.
+    /// assert!(!dot.contains("n0 -> n2"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_dot(&self, mut writer: impl Write) -> io::Result<()> {
+        let items = self.items();
+        let size = items.len();
+        let beats = |a: usize, b: usize| {
+            let record = self.record(items[a], items[b]);
+            record.wins > record.losses
+        };
+
+        // Whether there is a path of majority wins from every item to every other item.
+        let mut reachable: Vec<Vec<bool>> = (0..size)
+            .map(|a| (0..size).map(|b| a != b && beats(a, b)).collect())
+            .collect();
+        for via in 0..size {
+            let from_via = reachable[via].clone();
+            for row in reachable.iter_mut().filter(|row| row[via]) {
+                for (to, reachable_from_via) in row.iter_mut().zip(&from_via) {
+                    *to |= *reachable_from_via;
+                }
+            }
+        }
+        let in_cycle = |a: usize, b: usize| a == b || (reachable[a][b] && reachable[b][a]);
+
+        writeln!(writer, "digraph preferences {{")?;
+        writeln!(writer, "    rankdir=TB;")?;
+        for (index, item) in items.iter().enumerate() {
+            let highlight = if (0..size).any(|other| other != index && in_cycle(index, other)) {
+                ", color=red, fontcolor=red"
+            } else {
+                ""
+            };
+            writeln!(
+                writer,
+                "    n{} [label={}{}];",
+                index,
+                dot_string(item),
+                highlight
+            )?;
+        }
+        for a in 0..size {
+            for b in (0..size).filter(|b| *b != a && beats(a, *b)) {
+                let cycle = in_cycle(a, b);
+                // Leave out edges implied by a path through items outside of both their cycles.
+                let implied = !cycle
+                    && (0..size).any(|via| {
+                        !in_cycle(via, a)
+                            && !in_cycle(via, b)
+                            && reachable[a][via]
+                            && reachable[via][b]
+                    });
+                if implied {
+                    continue;
+                }
+
+                let record = self.record(items[a], items[b]);
+                let highlight = if cycle {
+                    ", color=red, fontcolor=red, penwidth=2"
+                } else {
+                    ""
+                };
+                writeln!(
+                    writer,
+                    "    n{} -> n{} [label=\"{}:{}\"{}];",
+                    a, b, record.wins, record.losses, highlight
+                )?;
+            }
+        }
+        writeln!(writer, "}}")?;
+        writer.flush()
+    }
+}
+
+/// Format a value as a quoted DOT string.
+fn dot_string(value: impl Display) -> String {
+    let value = value.to_string();
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            char => escaped.push(char),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ComparisonResult, HeadToHead, Item};
+
+    fn dot(head_to_head: &HeadToHead<usize>) -> String {
+        let mut dot = Vec::new();
+        head_to_head.write_dot(&mut dot).unwrap();
+        String::from_utf8(dot).unwrap()
+    }
+
+    #[test]
+    fn total_orders_reduce_to_a_chain() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let mut head_to_head = HeadToHead::new(items.iter());
+        for a in 0..5 {
+            for b in a + 1..5 {
+                head_to_head.track(&ComparisonResult::new(&items[a], &items[b]).into());
+            }
+        }
+
+        let dot = dot(&head_to_head);
+        let edges: Vec<_> = dot.lines().filter(|line| line.contains("->")).collect();
+        assert_eq!(
+            edges,
+            vec![
+                "    n0 -> n1 [label=\"1:0\"];",
+                "    n1 -> n2 [label=\"1:0\"];",
+                "    n2 -> n3 [label=\"1:0\"];",
+                "    n3 -> n4 [label=\"1:0\"];",
+            ]
+        );
+        assert!(!dot.contains("red"));
+    }
+
+    #[test]
+    fn cycles_are_highlighted_and_kept() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let mut head_to_head = HeadToHead::new(items.iter());
+        for (winner, loser) in [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (0, 3),
+            (1, 3),
+            (2, 3),
+            (2, 3),
+            (3, 2),
+        ] {
+            head_to_head.track(&ComparisonResult::new(&items[winner], &items[loser]).into());
+        }
+
+        let dot = dot(&head_to_head);
+        for edge in ["n0 -> n1", "n1 -> n2", "n2 -> n0"] {
+            let line = dot.lines().find(|line| line.contains(edge)).unwrap();
+            assert!(line.contains("color=red"), "{}", line);
+        }
+        // Every item of the cycle beats the last item directly, there is no path in between.
+        assert!(dot.contains("    n2 -> n3 [label=\"2:1\"];"));
+        assert!(dot.contains("    n3 [label=\"3\"];"));
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+
+/// A single event of a session recorded in an [`EventLog`](EventLog).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<T> {
+    /// An item that is part of the session.
+    Item(T),
+    /// The result of a comparison.
+    Result {
+        /// The item that won the comparison.
+        winner: T,
+        /// The item that lost the comparison.
+        loser: T,
+    },
+    /// A comparison that ended in a draw.
+    Draw {
+        /// The item shown on the left.
+        left: T,
+        /// The item shown on the right.
+        right: T,
+    },
+    /// A comparison that was skipped without an answer.
+    Skip {
+        /// The item shown on the left.
+        left: T,
+        /// The item shown on the right.
+        right: T,
+    },
+}
+
+/// A log of the events of a session, which can be written to and read from a plain-text file.
+///
+/// The log refers to items by their values, such that it can be persisted and loaded independently
+/// of the [`Item`s](crate::Item) of a session. It can, for example, serve as a
+/// [`Prior`](crate::Prior) for a new session.
+///
+/// ## Format
+///
+/// Every event is written as a single line of tab-separated fields, the first field naming the kind
+/// of event (tabs shown as `→`):
+///
+/// ```text
+/// item→Rust
+/// item→C++
+/// result→Rust→C++
+/// draw→C++→Java
+/// skip→Rust→Java
+/// ```
+///
+/// Backslashes, tabs and line breaks within values are escaped as `\\`, `\t`, `\n` and `\r`. Empty
+/// lines and lines starting with `#` are ignored.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Event, EventLog};
+/// let mut event_log = EventLog::new();
+/// event_log.push(Event::Item("Rust".to_owned()));
+/// event_log.push(Event::Item("C++".to_owned()));
+/// event_log.push(Event::Result {
+///     winner: "Rust".to_owned(),
+///     loser: "C++".to_owned(),
+/// });
+///
+/// let mut file = Vec::new();
+/// event_log.write_to(&mut file)?;
+/// let read: EventLog<String> = EventLog::read_from(&file[..])?;
+/// assert_eq!(read, event_log);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventLog<T> {
+    events: Vec<Event<T>>,
+}
+
+impl<T> Default for EventLog<T> {
+    fn default() -> Self {
+        Self { events: Vec::new() }
+    }
+}
+
+impl<T> EventLog<T> {
+    /// Create a new, empty event log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an event to the log.
+    pub fn push(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    /// All events of the log, in the order they were recorded.
+    pub fn events(&self) -> &[Event<T>] {
+        &self.events
+    }
+
+    /// All items of the log, in the order they were recorded.
+    pub fn items(&self) -> impl Iterator<Item = &T> {
+        self.events.iter().filter_map(|event| match event {
+            Event::Item(item) => Some(item),
+            _ => None,
+        })
+    }
+
+    /// All results of the log as `(winner, loser)`, in the order they were recorded.
+    ///
+    /// Draws and skipped comparisons are not results and thus left out.
+    pub fn results(&self) -> impl Iterator<Item = (&T, &T)> {
+        self.events.iter().filter_map(|event| match event {
+            Event::Result { winner, loser } => Some((winner, loser)),
+            _ => None,
+        })
+    }
+}
+
+impl<T: Display> EventLog<T> {
+    /// Write the log in its plain-text format.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        for event in &self.events {
+            match event {
+                Event::Item(item) => writeln!(writer, "item\t{}", escape(item)),
+                Event::Result { winner, loser } => {
+                    writeln!(writer, "result\t{}\t{}", escape(winner), escape(loser))
+                }
+                Event::Draw { left, right } => {
+                    writeln!(writer, "draw\t{}\t{}", escape(left), escape(right))
+                }
+                Event::Skip { left, right } => {
+                    writeln!(writer, "skip\t{}\t{}", escape(left), escape(right))
+                }
+            }?;
+        }
+        writer.flush()
+    }
+}
+
+impl<T: FromStr> EventLog<T> {
+    /// Read a log in its plain-text format.
+    pub fn read_from(reader: impl BufRead) -> Result<Self, EventLogError> {
+        let mut event_log = Self::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |reason: &str| EventLogError::InvalidLine {
+                line: index + 1,
+                reason: reason.to_owned(),
+            };
+            let parse = |field: &str| {
+                unescape(field)
+                    .ok_or_else(|| invalid("invalid escape sequence"))?
+                    .parse()
+                    .map_err(|_| invalid("invalid value"))
+            };
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            let event = match fields[..] {
+                ["item", item] => Event::Item(parse(item)?),
+                ["result", winner, loser] => Event::Result {
+                    winner: parse(winner)?,
+                    loser: parse(loser)?,
+                },
+                ["draw", left, right] => Event::Draw {
+                    left: parse(left)?,
+                    right: parse(right)?,
+                },
+                ["skip", left, right] => Event::Skip {
+                    left: parse(left)?,
+                    right: parse(right)?,
+                },
+                _ => return Err(invalid("unknown event or wrong number of fields")),
+            };
+            event_log.push(event);
+        }
+        Ok(event_log)
+    }
+}
+
+/// An error that occurred while reading an [`EventLog`](EventLog).
+#[derive(Debug)]
+pub enum EventLogError {
+    /// Reading from the underlying reader failed.
+    Io(io::Error),
+    /// A line of the log could not be parsed.
+    InvalidLine {
+        /// The number of the line, starting at `1`.
+        line: usize,
+        /// Why the line could not be parsed.
+        reason: String,
+    },
+}
+
+impl Display for EventLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to read the event log: {}", error),
+            Self::InvalidLine { line, reason } => {
+                write!(f, "invalid event log line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+impl Error for EventLogError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::InvalidLine { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for EventLogError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+fn escape(value: impl Display) -> String {
+    let value = value.to_string();
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+        unescaped.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn special_characters_roundtrip() {
+        let mut event_log = EventLog::new();
+        event_log.push(Event::Item("tab\there".to_owned()));
+        event_log.push(Event::Item("line\nbreak \\n".to_owned()));
+        event_log.push(Event::Result {
+            winner: "tab\there".to_owned(),
+            loser: "line\nbreak \\n".to_owned(),
+        });
+
+        let mut written = Vec::new();
+        event_log.write_to(&mut written).unwrap();
+        assert_eq!(String::from_utf8_lossy(&written).lines().count(), 3);
+        assert_eq!(
+            EventLog::<String>::read_from(&written[..]).unwrap(),
+            event_log
+        );
+    }
+
+    #[test]
+    fn comments_and_empty_lines_are_ignored() {
+        let event_log: EventLog<usize> =
+            EventLog::read_from("# A comment\n\nitem\t1\nitem\t2\nresult\t2\t1\n".as_bytes())
+                .unwrap();
+
+        assert_eq!(event_log.items().copied().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(event_log.results().collect::<Vec<_>>(), vec![(&2, &1)]);
+
+        let event_log: EventLog<usize> =
+            EventLog::read_from("draw\t1\t2\nskip\t2\t3\n".as_bytes()).unwrap();
+        assert_eq!(
+            event_log.events(),
+            &[
+                Event::Draw { left: 1, right: 2 },
+                Event::Skip { left: 2, right: 3 }
+            ]
+        );
+        assert_eq!(event_log.results().count(), 0);
+    }
+
+    #[test]
+    fn invalid_lines_are_reported() {
+        for (input, line) in [
+            ("item\t1\nresult\t1\n", 2),
+            ("item\tnot-a-number\n", 1),
+            ("item\t1\\x\n", 1),
+            ("\nunknown\t1\n", 2),
+        ] {
+            match EventLog::<usize>::read_from(input.as_bytes()) {
+                Err(EventLogError::InvalidLine { line: actual, .. }) => assert_eq!(actual, line),
+                result => panic!("unexpected result for {:?}: {:?}", input, result),
+            }
+        }
+    }
+}
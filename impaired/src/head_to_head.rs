@@ -0,0 +1,349 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{Decision, Item};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+    io::{self, Write},
+};
+
+/// The results of an item against another item.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    /// How often the item won against the other item.
+    pub wins: usize,
+    /// How often the item lost against the other item.
+    pub losses: usize,
+    /// How often the comparison between the items was a draw.
+    pub draws: usize,
+}
+
+impl Record {
+    /// How often the items were compared.
+    pub fn total(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+}
+
+/// The matrix of how every item fared against every other item.
+///
+/// Contrary to the score of every item, which only counts its wins, the matrix keeps who beat whom,
+/// and can be exported as CSV or JSON for further analysis. The [`Scores`](crate::Scores) keep such
+/// a matrix alongside the scores, see [`Scores::head_to_head`](crate::Scores::head_to_head).
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{ComparisonResult, HeadToHead, Item};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+///
+/// let mut head_to_head = HeadToHead::new([&rust, &cpp]);
+/// head_to_head.track(&ComparisonResult::new(&rust, &cpp).into());
+/// head_to_head.track(&ComparisonResult::new(&rust, &cpp).into());
+/// head_to_head.track(&ComparisonResult::new(&cpp, &rust).into());
+///
+/// let record = head_to_head.record(&rust, &cpp);
+/// assert_eq!((record.wins, record.losses, record.draws), (2, 1, 0));
+///
+/// let mut csv = Vec::new();
+/// head_to_head.write_csv(&mut csv)?;
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     "item,opponent,wins,losses,draws\nRust,C++,2,1,0\nC++,Rust,1,2,0\n"
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct HeadToHead<'a, T> {
+    items: Vec<&'a Item<T>>,
+    known: HashSet<&'a Item<T>>,
+    /// How often the first item of every pair won against the second one.
+    wins: HashMap<(&'a Item<T>, &'a Item<T>), usize>,
+    /// How often every pair of items drew, stored in both orders.
+    draws: HashMap<(&'a Item<T>, &'a Item<T>), usize>,
+}
+
+impl<'a, T> Default for HeadToHead<'a, T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            known: HashSet::new(),
+            wins: HashMap::new(),
+            draws: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, T> Clone for HeadToHead<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            known: self.known.clone(),
+            wins: self.wins.clone(),
+            draws: self.draws.clone(),
+        }
+    }
+}
+
+impl<'a, T: Eq + Hash> HeadToHead<'a, T> {
+    /// Create an empty matrix for the given items, in the order they should be listed in.
+    pub fn new(items: impl IntoIterator<Item = &'a Item<T>>) -> Self {
+        let mut head_to_head = Self::default();
+        for item in items {
+            head_to_head.register(item);
+        }
+        head_to_head
+    }
+
+    /// Take a decision into account.
+    ///
+    /// Items that are not part of the matrix yet are appended to it.
+    pub fn track(&mut self, decision: &Decision<'a, T>)
+    where
+        T: Ord,
+    {
+        match decision.result() {
+            Some(result) => self.track_result(result.winner, result.loser),
+            None => self.track_draw(decision.comparison.left, decision.comparison.right),
+        }
+    }
+
+    /// All items of the matrix, in the order they are listed in.
+    pub fn items(&self) -> &[&'a Item<T>] {
+        &self.items
+    }
+
+    /// The results of item `a` against item `b`.
+    pub fn record(&self, a: &Item<T>, b: &Item<T>) -> Record {
+        let count = |counts: &HashMap<_, usize>, pair| counts.get(&pair).copied().unwrap_or(0);
+        Record {
+            wins: count(&self.wins, (a, b)),
+            losses: count(&self.wins, (b, a)),
+            draws: count(&self.draws, (a, b)),
+        }
+    }
+
+    /// The total record of an item against all other items.
+    pub fn total(&self, item: &Item<T>) -> Record {
+        self.items
+            .iter()
+            .map(|other| self.record(item, other))
+            .fold(Record::default(), |total, record| Record {
+                wins: total.wins + record.wins,
+                losses: total.losses + record.losses,
+                draws: total.draws + record.draws,
+            })
+    }
+
+    /// Append the item to the matrix, unless it is part of it already.
+    pub(crate) fn register(&mut self, item: &'a Item<T>) {
+        if self.known.insert(item) {
+            self.items.push(item);
+        }
+    }
+
+    pub(crate) fn track_result(&mut self, winner: &'a Item<T>, loser: &'a Item<T>) {
+        self.register(winner);
+        self.register(loser);
+        *self.wins.entry((winner, loser)).or_default() += 1;
+    }
+
+    pub(crate) fn track_draw(&mut self, a: &'a Item<T>, b: &'a Item<T>) {
+        self.register(a);
+        self.register(b);
+        *self.draws.entry((a, b)).or_default() += 1;
+        *self.draws.entry((b, a)).or_default() += 1;
+    }
+
+    /// Every pair of items where the first one won against the second one, along with how often.
+    pub(crate) fn wins(&self) -> impl Iterator<Item = (&'a Item<T>, &'a Item<T>, usize)> + '_ {
+        self.wins
+            .iter()
+            .map(|((winner, loser), count)| (*winner, *loser, *count))
+    }
+
+    /// The same matrix with the given items listed first, in the given order, followed by the
+    /// remaining items in the order they were listed in before.
+    pub(crate) fn ordered(&self, items: impl IntoIterator<Item = &'a Item<T>>) -> Self {
+        let mut ordered = Self {
+            items: Vec::with_capacity(self.items.len()),
+            known: HashSet::with_capacity(self.known.len()),
+            wins: self.wins.clone(),
+            draws: self.draws.clone(),
+        };
+        for item in items.into_iter().chain(self.items.iter().copied()) {
+            ordered.register(item);
+        }
+        ordered
+    }
+}
+
+impl<'a, T: Display + Eq + Hash> HeadToHead<'a, T> {
+    /// Write the matrix as CSV, with one row per ordered pair of distinct items.
+    ///
+    /// The columns are `item`, `opponent`, `wins`, `losses` and `draws`, from the perspective of
+    /// `item`. Every pair is thus listed twice, once from either side.
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "item,opponent,wins,losses,draws")?;
+        for item in &self.items {
+            for opponent in &self.items {
+                if item == opponent {
+                    continue;
+                }
+                let record = self.record(item, opponent);
+                writeln!(
+                    writer,
+                    "{},{},{},{},{}",
+                    csv_field(item),
+                    csv_field(opponent),
+                    record.wins,
+                    record.losses,
+                    record.draws
+                )?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Write the matrix as JSON.
+    ///
+    /// The JSON object contains the `items` in the order they are listed in, and the `wins`,
+    /// `losses` and `draws` as matrices, where e.g. `wins[a][b]` is how often item `a` won against
+    /// item `b`.
+    pub fn write_json(&self, mut writer: impl Write) -> io::Result<()> {
+        let items: Vec<String> = self.items.iter().map(json_string).collect();
+        let matrix = |value: &dyn Fn(Record) -> usize| -> String {
+            let rows: Vec<String> = self
+                .items
+                .iter()
+                .map(|a| {
+                    let row: Vec<String> = self
+                        .items
+                        .iter()
+                        .map(|b| value(self.record(a, b)).to_string())
+                        .collect();
+                    format!("[{}]", row.join(","))
+                })
+                .collect();
+            format!("[{}]", rows.join(","))
+        };
+        writeln!(
+            writer,
+            "{{\"items\":[{}],\"wins\":{},\"losses\":{},\"draws\":{}}}",
+            items.join(","),
+            matrix(&|record| record.wins),
+            matrix(&|record| record.losses),
+            matrix(&|record| record.draws),
+        )?;
+        writer.flush()
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: impl Display) -> String {
+    let value = value.to_string();
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Format a value as a JSON string literal.
+pub(crate) fn json_string(value: impl Display) -> String {
+    let value = value.to_string();
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if char.is_control() => escaped.push_str(&format!("\\u{:04x}", char as u32)),
+            char => escaped.push(char),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Comparison, ComparisonResult, Outcome, Scores};
+
+    #[test]
+    fn records_are_symmetric() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut head_to_head = HeadToHead::new([&items[0], &items[1]]);
+        head_to_head.track(&ComparisonResult::new(&items[0], &items[1]).into());
+        head_to_head.track(&Decision {
+            comparison: Comparison::new(&items[1], &items[2]),
+            outcome: Outcome::Draw,
+        });
+
+        assert_eq!(head_to_head.items(), &[&items[0], &items[1], &items[2]]);
+        assert_eq!(
+            head_to_head.record(&items[1], &items[0]),
+            Record {
+                wins: 0,
+                losses: 1,
+                draws: 0
+            }
+        );
+        assert_eq!(head_to_head.record(&items[2], &items[1]).draws, 1);
+        assert_eq!(head_to_head.total(&items[1]).total(), 2);
+    }
+
+    #[test]
+    fn scores_keep_the_matrix() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut scores = Scores::new();
+        scores.track(&items[2], &items[0]);
+        scores.track(&items[1], &items[2]);
+
+        let head_to_head = scores.head_to_head();
+        assert_eq!(head_to_head.items(), &[&items[2], &items[0], &items[1]]);
+        assert_eq!(head_to_head.record(&items[2], &items[0]).wins, 1);
+        assert_eq!(head_to_head.total(&items[2]).total(), 2);
+
+        let ordered = head_to_head.ordered([&items[1], &items[0]]);
+        assert_eq!(ordered.items(), &[&items[1], &items[0], &items[2]]);
+        assert_eq!(ordered.record(&items[1], &items[2]).wins, 1);
+    }
+
+    #[test]
+    fn exports_escape_items() {
+        let items = [Item("a, \"b\""), Item("c\nd")];
+        let mut head_to_head = HeadToHead::new(items.iter());
+        head_to_head.track(&ComparisonResult::new(&items[1], &items[0]).into());
+
+        let mut csv = Vec::new();
+        head_to_head.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "item,opponent,wins,losses,draws\n\
+             \"a, \"\"b\"\"\",\"c\nd\",0,1,0\n\
+             \"c\nd\",\"a, \"\"b\"\"\",1,0,0\n"
+        );
+
+        let mut json = Vec::new();
+        head_to_head.write_json(&mut json).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"items\":[\"a, \\\"b\\\"\",\"c\\nd\"],\
+             \"wins\":[[0,0],[1,0]],\"losses\":[[0,1],[0,0]],\"draws\":[[0,0],[0,0]]}\n"
+        );
+    }
+}
@@ -10,16 +10,63 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../../README.md")]
 
+use pair_set::PairSet;
 use std::{
     cell::RefCell,
     cmp,
-    collections::{HashMap, HashSet},
+    collections::HashMap,
     fmt::{Debug, Display, Formatter},
     hash::{Hash, Hasher},
-    ops::{Deref, DerefMut},
+    iter,
+    ops::Deref,
     rc::Rc,
 };
 
+pub use aggregation::Aggregation;
+pub use agreement::Ranks;
+pub use ahp::{Ahp, Priorities};
+pub use bootstrap::{Bootstrap, RankInterval};
+pub use bradley_terry::BradleyTerry;
+pub use bucketing::Bucketing;
+pub use budget::BudgetIterator;
+pub use constraints::{ConstraintError, Constraints};
+pub use dispatcher::Dispatcher;
+pub use event_log::{Event, EventLog, EventLogError};
+pub use head_to_head::{HeadToHead, Record};
+pub use multi_criteria::MultiCriteria;
+pub use panel::{Panel, Vote};
+pub use pivot::PivotIterator;
+pub use prior::{Prior, WarmStart};
+pub use ranking::{Ranked, Ranking};
+pub use report::{Consistency, Report};
+pub use scorer::{Aggregated, Scorer};
+pub use session::{Decision, Outcome, Session, SessionError};
+pub use strategy::{RetainWinner, SessionState, Strategy};
+
+pub mod aggregation;
+pub mod agreement;
+mod ahp;
+mod bootstrap;
+mod bradley_terry;
+mod bucketing;
+mod budget;
+mod constraints;
+mod dispatcher;
+mod dot;
+mod event_log;
+mod head_to_head;
+mod multi_criteria;
+mod pair_set;
+mod panel;
+mod pivot;
+mod prior;
+mod ranking;
+mod report;
+mod scorer;
+mod session;
+mod strategy;
+pub mod svg;
+
 /// An item for use in pairwise comparisons.
 ///
 /// ```rust
@@ -83,10 +130,7 @@ pub struct Comparison<'a, T: Eq + Hash + Ord> {
 
 impl<'a, T: Eq + Hash + Ord> Clone for Comparison<'a, T> {
     fn clone(&self) -> Self {
-        Self {
-            left: self.left,
-            right: self.right,
-        }
+        *self
     }
 }
 
@@ -118,7 +162,19 @@ impl<'a, T: Eq + Hash + Ord> Hash for Comparison<'a, T> {
 }
 
 impl<'a, T: Eq + Hash + Ord> Comparison<'a, T> {
-    fn other(&self, item: &'a Item<T>) -> &'a Item<T> {
+    /// Get the item of the comparison that is not the given item.
+    ///
+    /// If the given item is not part of the comparison, the left item is returned.
+    ///
+    /// ```rust
+    /// # use impaired::{Comparison, Item};
+    /// let rust = Item("Rust");
+    /// let cpp = Item("C++");
+    /// let comparison = Comparison::new(&rust, &cpp);
+    /// assert_eq!(comparison.other(&rust), &cpp);
+    /// assert_eq!(comparison.other(&cpp), &rust);
+    /// ```
+    pub fn other(&self, item: &Item<T>) -> &'a Item<T> {
         if self.left == item {
             self.right
         } else {
@@ -127,17 +183,24 @@ impl<'a, T: Eq + Hash + Ord> Comparison<'a, T> {
     }
 }
 
-/// A list of comparisons.
+/// A set of comparisons.
 ///
-/// This is a thin wrapper around a [`Vec`](std::vec::Vec) of [`Comparison`s](Comparison).
-#[derive(Debug, Default)]
-pub struct Comparisons<'a, T: Eq + Hash + Ord>(HashSet<Comparison<'a, T>>);
+/// The comparisons are stored as a bitset over the pairs of the items they were created from,
+/// requiring a single bit per comparison, such that even large numbers of items can be compared
+/// without excessive memory usage. The [`Comparison`s](Comparison) themselves are only created
+/// while iterating.
+#[derive(Debug)]
+pub struct Comparisons<'a, T: Eq + Hash + Ord> {
+    items: Vec<&'a Item<T>>,
+    pairs: PairSet,
+}
 
 impl<'a, T: Eq + Hash + Ord> Comparisons<'a, T> {
     /// Create a new set of comparisons from a list of [`Item`s](Item).
     ///
     /// The comparisons created will be exhaustive across the list of items provided, ensuring that
-    /// for each provided item there is exactly one comparison against every other item.
+    /// for each provided item there is exactly one comparison against every other item. Items that
+    /// are provided multiple times are only considered once.
     ///
     /// ```rust
     /// # use impaired::{Comparison, Comparisons, Item};
@@ -147,16 +210,14 @@ impl<'a, T: Eq + Hash + Ord> Comparisons<'a, T> {
     /// let java = Item("Java");
     /// let comparisons = Comparisons::new([&rust, &cpp, &java]);
     /// assert_eq!(comparisons.len(), 3);
-    /// assert_eq!(*comparisons, [
+    /// assert_eq!(comparisons.iter().collect::<HashSet<_>>(), [
     ///     Comparison::new(&java, &rust),
     ///     Comparison::new(&java, &cpp),
     ///     Comparison::new(&cpp, &rust),
     /// ].into());
     /// ```
     ///
-    /// `Comparisons` automatically dereferences into the underlying `HashSet` of
-    /// [`Comparison`s](Comparison), such that you can interact with the comparisons, e.g. for
-    /// iteration:
+    /// You can iterate over the comparisons directly:
     ///
     /// ```rust
     /// # use impaired::{Comparisons, Item};
@@ -171,41 +232,116 @@ impl<'a, T: Eq + Hash + Ord> Comparisons<'a, T> {
     ///
     /// ## Panics
     ///
-    /// Calling this function panics if you don't provide at least two items.
+    /// Calling this function panics if you don't provide at least two distinct items.
+    pub fn new(items: impl IntoIterator<Item = &'a Item<T>>) -> Self {
+        let mut items: Vec<&'a Item<T>> = items.into_iter().collect();
+        items.sort_unstable();
+        items.dedup();
+
+        if items.len() < 2 {
+            panic!("at least two items are required to construct the comparisons");
+        }
+
+        let pairs = PairSet::full(items.len());
+        Self { items, pairs }
+    }
+
+    /// The number of comparisons.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Whether there are no comparisons.
     ///
-    /// ## Order of comparisons
+    /// This can only be the case for comparisons derived from other comparisons, e.g. through
+    /// [`Comparisons::retain`](Comparisons::retain).
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Whether the given comparison is part of the comparisons.
+    pub fn contains(&self, comparison: &Comparison<'a, T>) -> bool {
+        match (self.index(comparison.left), self.index(comparison.right)) {
+            (Some(left), Some(right)) => self.pairs.contains(left, right),
+            _ => false,
+        }
+    }
+
+    /// Iterate over the comparisons.
     ///
-    /// Currently there is no guarantee about the order of the items returned. Do not rely on the
-    /// order in your implementation.
+    /// ## Order of comparisons
     ///
-    /// If you need to follow a specific order, you can dereference the comparisons into the inner
-    /// [`HashSet`](std::collections::HashSet) of [`Comparison`](Comparison) and then do what is
-    /// necessary to follow the specific order you need.
+    /// The comparisons are returned in a deterministic order, but there is no guarantee about which
+    /// order that is. Do not rely on the order in your implementation, but collect and sort the
+    /// comparisons if you need to follow a specific order.
+    pub fn iter(&self) -> impl Iterator<Item = Comparison<'a, T>> + '_ {
+        self.pairs
+            .iter()
+            .map(move |(left, right)| Comparison::new(self.items[left], self.items[right]))
+    }
+
+    /// Remove a comparison, returning whether it was part of the comparisons.
+    pub fn remove(&mut self, comparison: &Comparison<'a, T>) -> bool {
+        match (self.index(comparison.left), self.index(comparison.right)) {
+            (Some(left), Some(right)) => self.pairs.remove(left, right),
+            _ => false,
+        }
+    }
+
+    /// Add a comparison between two of the items the comparisons were created from again, returning
+    /// whether it was added.
+    pub(crate) fn insert(&mut self, comparison: &Comparison<'a, T>) -> bool {
+        match (self.index(comparison.left), self.index(comparison.right)) {
+            (Some(left), Some(right)) => self.pairs.insert(left, right),
+            _ => false,
+        }
+    }
+
+    /// Retain only the comparisons for which the predicate returns `true`.
     ///
     /// ```rust
-    /// # use impaired::{Comparison, Comparisons, Item};
-    /// # use std::collections::HashSet;
-    /// # let rust = Item("Rust");
-    /// # let cpp = Item("C++");
-    /// # let java = Item("Java");
-    /// let comparisons = Comparisons::new([&rust, &cpp, &java]);
-    /// let inner: &HashSet<Comparison<&str>> = &*comparisons;
-    /// # assert_eq!(inner.len(), 3);
+    /// # use impaired::{Comparisons, Item};
+    /// let rust = Item("Rust");
+    /// let cpp = Item("C++");
+    /// let java = Item("Java");
+    /// let mut comparisons = Comparisons::new([&rust, &cpp, &java]);
+    /// comparisons.retain(|comparison| comparison.left != &java && comparison.right != &java);
+    /// assert_eq!(comparisons.len(), 1);
+    /// assert_eq!(comparisons.items(), vec![&cpp, &rust]);
     /// ```
-    pub fn new(items: impl IntoIterator<Item = &'a Item<T>>) -> Self {
-        let mut comparisons = HashSet::new();
-        let mut it: Vec<&'a Item<T>> = items.into_iter().collect();
-        while let Some(item) = it.pop() {
-            for other in &it {
-                comparisons.insert(Comparison::new(item, *other));
-            }
+    pub fn retain(&mut self, mut keep: impl FnMut(&Comparison<'a, T>) -> bool) {
+        let removed: Vec<(usize, usize)> = self
+            .pairs
+            .iter()
+            .filter(|(left, right)| !keep(&Comparison::new(self.items[*left], self.items[*right])))
+            .collect();
+        for (left, right) in removed {
+            self.pairs.remove(left, right);
         }
+    }
 
-        if comparisons.is_empty() {
-            panic!("at least two items are required to construct the comparisons");
+    /// Get all items that are part of at least one of the comparisons, in ascending order.
+    ///
+    /// ```rust
+    /// # use impaired::{Comparisons, Item};
+    /// let rust = Item("Rust");
+    /// let cpp = Item("C++");
+    /// let java = Item("Java");
+    /// let comparisons = Comparisons::new([&rust, &cpp, &java]);
+    /// assert_eq!(comparisons.items(), vec![&cpp, &java, &rust]);
+    /// ```
+    pub fn items(&self) -> Vec<&'a Item<T>> {
+        // Every item is part of a comparison as long as none were removed.
+        let items = self.items.len();
+        if self.pairs.len() == items * items.saturating_sub(1) / 2 {
+            return self.items.clone();
         }
-
-        Self(comparisons)
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.pairs.partner(*index).is_some())
+            .map(|(_, item)| *item)
+            .collect()
     }
 
     /// Get an iterator over the comparisons such that every comparison returned after the first
@@ -216,40 +352,166 @@ impl<'a, T: Eq + Hash + Ord> Comparisons<'a, T> {
     /// know which item won. If you track the winner, the iterator will preferably continue with a
     /// comparison that contains the item that just won. If you do not track the winner, one of the
     /// two items of the comparison is guaranteed to stay the same across comparisons, although
-    /// which one stays is not guaranteed then.
+    /// which one stays is not guaranteed then. Only once neither item has a comparison left does
+    /// the iterator continue with an unrelated comparison.
     ///
     /// For more details see [`RetainItemIterator`](RetainItemIterator).
-    pub fn retain_item_iterator(&self) -> RetainItemIterator<T> {
+    pub fn retain_item_iterator(&self) -> RetainItemIterator<'a, T> {
         RetainItemIterator::new(self)
     }
+
+    /// Get an iterator yielding at most `budget` comparisons, chosen such that the ranking is as
+    /// informative as possible within that budget.
+    ///
+    /// Use this over truncating another iterator if you know up front that only a limited number of
+    /// comparisons will be answered, e.g. because the people answering them will not answer more
+    /// than a certain number of questions regardless of how many items there are.
+    ///
+    /// For more details see [`BudgetIterator`](BudgetIterator).
+    pub fn budget_iterator(&self, budget: usize) -> BudgetIterator<'a, T> {
+        BudgetIterator::new(self, budget)
+    }
+
+    /// Get an iterator comparing items against a pivot item, partitioning them recursively like
+    /// quicksort.
+    ///
+    /// Comparing every item against the same reference item is cognitively easier than comparing
+    /// random pairs, and optionally stopping at a maximum depth results in a partial order with
+    /// far fewer comparisons.
+    ///
+    /// For more details see [`PivotIterator`](PivotIterator).
+    pub fn pivot_iterator(&self) -> PivotIterator<'a, T> {
+        PivotIterator::new(self)
+    }
+
+    /// The index of an item within the sorted items the comparisons were created from.
+    fn index(&self, item: &Item<T>) -> Option<usize> {
+        self.items.binary_search(&item).ok()
+    }
 }
 
-impl<'a, T: Eq + Hash + Ord> Deref for Comparisons<'a, T> {
-    type Target = HashSet<Comparison<'a, T>>;
+impl<'a, T: Eq + Hash + Ord> Clone for Comparisons<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            pairs: self.pairs.clone(),
+        }
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl<'a, T: Eq + Hash + Ord> Default for Comparisons<'a, T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            pairs: PairSet::full(0),
+        }
     }
 }
 
-struct ComparisonResult<'a, T: Eq + Hash + Ord> {
-    comparison: Comparison<'a, T>,
-    winner: &'a Item<T>,
-    loser: &'a Item<T>,
+/// The result of a single comparison, i.e. which of the two compared items won.
+///
+/// ```rust
+/// # use impaired::{Comparison, ComparisonResult, Item};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let result = ComparisonResult::new(&rust, &cpp);
+/// assert_eq!(result.comparison, Comparison::new(&cpp, &rust));
+/// assert_eq!(result.winner, &rust);
+/// assert_eq!(result.loser, &cpp);
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct ComparisonResult<'a, T: Eq + Hash + Ord> {
+    /// The comparison this is the result of.
+    pub comparison: Comparison<'a, T>,
+    /// The item that won the comparison.
+    pub winner: &'a Item<T>,
+    /// The item that lost the comparison.
+    pub loser: &'a Item<T>,
 }
 
-impl<'a, T: Eq + Hash + Ord> Clone for ComparisonResult<'a, T> {
-    fn clone(&self) -> Self {
+impl<'a, T: Eq + Hash + Ord> ComparisonResult<'a, T> {
+    /// Create the result of a comparison between `winner` and `loser`.
+    pub fn new(winner: &'a Item<T>, loser: &'a Item<T>) -> Self {
         Self {
-            comparison: self.comparison,
-            winner: self.winner,
-            loser: self.loser,
+            comparison: Comparison::new(winner, loser),
+            winner,
+            loser,
         }
     }
 }
 
+impl<'a, T: Eq + Hash + Ord> Clone for ComparisonResult<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
 impl<'a, T: Eq + Hash + Ord> Copy for ComparisonResult<'a, T> {}
 
+/// A criterion deciding when an iterator can stop yielding comparisons before all of them have been
+/// exhausted.
+///
+/// Iterators such as the [`RetainItemIterator`](RetainItemIterator) count the winners tracked
+/// through them. Combined with the number of comparisons each item still has left, this allows them
+/// to determine whether the outcome of the remaining comparisons could still change the top of the
+/// ranking, which is the case this criterion is concerned with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoppingCriterion {
+    /// Never stop early, i.e. yield every single comparison.
+    Exhaustive,
+    /// Stop once the first `n` places of the ranking by win count are settled.
+    ///
+    /// A place is settled once the item holding it has more wins than any item below it could still
+    /// reach if it won all of its remaining comparisons. `TopSettled(1)` thus stops as soon as the
+    /// leader cannot be overtaken (or tied) anymore.
+    ///
+    /// Comparisons for which no winner was tracked count as asked, but credit neither item.
+    /// `TopSettled(0)` is not concerned with any place, it never stops early just like
+    /// [`Exhaustive`](StoppingCriterion::Exhaustive).
+    TopSettled(usize),
+}
+
+impl Default for StoppingCriterion {
+    fn default() -> Self {
+        Self::Exhaustive
+    }
+}
+
+impl StoppingCriterion {
+    /// Whether the criterion is met for the given items, given the wins they have according to
+    /// `scores` and the number of comparisons `remaining` for the item at every index.
+    pub(crate) fn is_met<'a, T: Eq + Hash>(
+        &self,
+        items: &[&'a Item<T>],
+        scores: &Scores<'a, T>,
+        remaining: impl Fn(usize) -> usize,
+    ) -> bool {
+        match self {
+            StoppingCriterion::Exhaustive | StoppingCriterion::TopSettled(0) => false,
+            StoppingCriterion::TopSettled(places) => {
+                // For every item, collect the number of wins it has for sure, and the number of wins
+                // it could at most reach if it won all of its remaining comparisons.
+                let mut standings: Vec<(usize, usize)> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let wins = scores.get(item).copied().unwrap_or_default();
+                        (wins, wins + remaining(index))
+                    })
+                    .collect();
+                standings.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+
+                (0..cmp::min(*places, standings.len())).all(|place| {
+                    let (wins, _) = standings[place];
+                    standings[place + 1..]
+                        .iter()
+                        .all(|(_, reachable_wins)| *reachable_wins < wins)
+                })
+            }
+        }
+    }
+}
+
 /// An iterator ensuring that exactly one item from a previous iteration's comparison is retained to
 /// subsequent iterations.
 ///
@@ -257,6 +519,8 @@ impl<'a, T: Eq + Hash + Ord> Copy for ComparisonResult<'a, T> {}
 /// [`ComparisonResultTracker`](ComparisonResultTracker), allowing the user of this iterator to
 /// track which item in a comparison has won, allowing the iterator to select subsequent iterations
 /// such that the winning item appears again (as long as there is a comparison left for that item).
+/// If neither item of a comparison has a comparison left, the iteration continues with any of the
+/// remaining comparisons, such that every comparison is yielded eventually.
 ///
 /// ## Example
 ///
@@ -273,43 +537,92 @@ impl<'a, T: Eq + Hash + Ord> Copy for ComparisonResult<'a, T> {}
 ///     result_tracker.winner(comparison.left);
 /// }
 /// ```
+///
+/// ## Stopping early
+///
+/// By default the iterator yields every comparison. If you are only interested in the top of the
+/// ranking, you can provide a [`StoppingCriterion`](StoppingCriterion) that ends the iteration as
+/// soon as the remaining comparisons cannot change the outcome anymore:
+///
+/// ```rust
+/// # use impaired::{Comparisons, Item, StoppingCriterion};
+/// let items: Vec<Item<usize>> = (0..10).map(Item).collect();
+/// let comparisons = Comparisons::new(items.iter());
+/// let iterator = comparisons
+///     .retain_item_iterator()
+///     .stopping_criterion(StoppingCriterion::TopSettled(1));
+///
+/// let mut asked = 0;
+/// for (comparison, result_tracker) in iterator {
+///     // The bigger number always wins.
+///     result_tracker.winner(std::cmp::max(comparison.left, comparison.right));
+///     asked += 1;
+/// }
+/// assert!(asked < comparisons.len());
+/// ```
 pub struct RetainItemIterator<'a, T: Eq + Hash + Ord> {
-    comparisons_by_item: HashMap<&'a Item<T>, HashSet<Comparison<'a, T>>>,
+    remaining: Comparisons<'a, T>,
     previous_comparison: Rc<RefCell<Option<Comparison<'a, T>>>>,
     previous_comparison_result: Rc<RefCell<Option<ComparisonResult<'a, T>>>>,
+    previous_comparison_recorded: bool,
+    stopping_criterion: StoppingCriterion,
+    scores: Scores<'a, T>,
 }
 
 impl<'a, T: Eq + Hash + Ord> RetainItemIterator<'a, T> {
     fn new(input: &Comparisons<'a, T>) -> Self {
-        let mut comparisons_by_item: HashMap<_, HashSet<_>> = HashMap::new();
-
-        for comparison in input.deref() {
-            comparisons_by_item
-                .entry(comparison.left)
-                .and_modify(|v| {
-                    v.insert(*comparison);
-                })
-                .or_insert_with(|| {
-                    let mut hashset = HashSet::new();
-                    hashset.insert(*comparison);
-                    hashset
-                });
-            comparisons_by_item
-                .entry(comparison.right)
-                .and_modify(|v| {
-                    v.insert(*comparison);
-                })
-                .or_insert_with(|| {
-                    let mut hashset = HashSet::new();
-                    hashset.insert(*comparison);
-                    hashset
-                });
-        }
-
         Self {
-            comparisons_by_item,
+            remaining: input.clone(),
             previous_comparison: Rc::new(RefCell::new(None)),
             previous_comparison_result: Rc::new(RefCell::new(None)),
+            previous_comparison_recorded: false,
+            stopping_criterion: StoppingCriterion::default(),
+            scores: Scores::new(),
+        }
+    }
+
+    /// Set the criterion that decides whether the iterator can stop before all comparisons have
+    /// been yielded.
+    ///
+    /// The criterion can only be evaluated based on the winners you track, either through the
+    /// [`ComparisonResultTracker`](ComparisonResultTracker) or
+    /// [`RetainItemIterator::winner`](RetainItemIterator::winner). See
+    /// [`StoppingCriterion`](StoppingCriterion) for the available criteria.
+    pub fn stopping_criterion(mut self, stopping_criterion: StoppingCriterion) -> Self {
+        self.stopping_criterion = stopping_criterion;
+        self
+    }
+
+    /// Whether the configured [`StoppingCriterion`](StoppingCriterion) is met, i.e. whether the
+    /// remaining comparisons cannot change the outcome the criterion is concerned with anymore.
+    ///
+    /// This is always `false` for [`StoppingCriterion::Exhaustive`].
+    pub fn is_settled(&self) -> bool {
+        self.stopping_criterion
+            .is_met(&self.remaining.items, &self.scores, |index| {
+                self.remaining.pairs.count(index)
+            })
+    }
+
+    fn record_previous_comparison_result(&mut self) {
+        if self.previous_comparison_recorded {
+            return;
+        }
+        self.previous_comparison_recorded = true;
+
+        let previous_comparison = *self.previous_comparison.borrow();
+        let previous_comparison_result = *self.previous_comparison_result.borrow();
+        if let (Some(previous_comparison), Some(previous_comparison_result)) =
+            (previous_comparison, previous_comparison_result)
+        {
+            // The result might still be the one of an earlier comparison if the winner of the
+            // previous comparison wasn't tracked.
+            if previous_comparison_result.comparison == previous_comparison {
+                self.scores.track(
+                    previous_comparison_result.winner,
+                    previous_comparison_result.loser,
+                );
+            }
         }
     }
 
@@ -400,6 +713,11 @@ impl<'a, T: Eq + Hash + Ord> Iterator for RetainItemIterator<'a, T> {
     type Item = (Comparison<'a, T>, ComparisonResultTracker<'a, T>);
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.record_previous_comparison_result();
+        if self.is_settled() {
+            return None;
+        }
+
         let (winner, loser) =
             if let Some(previous_comparison_result) = *self.previous_comparison_result.borrow() {
                 (
@@ -409,58 +727,28 @@ impl<'a, T: Eq + Hash + Ord> Iterator for RetainItemIterator<'a, T> {
             } else if let Some(previous_comparison) = *self.previous_comparison.borrow() {
                 (previous_comparison.left, previous_comparison.right)
             } else {
-                match self
-                    .comparisons_by_item
-                    .iter()
-                    .next()
-                    .expect("at least one item has to exist")
-                    .1
-                    .iter()
-                    .next()
-                    .copied()
-                {
+                // The comparisons might be empty if they were derived from other comparisons, e.g.
+                // through a warm start.
+                match self.remaining.iter().next() {
                     Some(seed_comparison) => (seed_comparison.left, seed_comparison.right),
                     None => return None,
                 }
             };
 
-        let winner_candidate = self
-            .comparisons_by_item
-            .get_mut(winner)
-            .expect("the referenced item has to exist")
-            .iter()
-            .next()
-            .copied();
-        let loser_candidate = self
-            .comparisons_by_item
-            .get_mut(loser)
-            .expect("the referenced item has to exist")
-            .iter()
-            .next()
-            .copied();
-
-        match (winner_candidate, loser_candidate) {
-            (Some(comparison), _) | (None, Some(comparison)) => {
-                self.comparisons_by_item
-                    .get_mut(comparison.left)
-                    .expect("the referenced item has to exist")
-                    .remove(&comparison);
-                self.comparisons_by_item
-                    .get_mut(comparison.right)
-                    .expect("the referenced item has to exist")
-                    .remove(&comparison);
-
-                self.previous_comparison.borrow_mut().replace(comparison);
-                Some((
-                    comparison,
-                    ComparisonResultTracker {
-                        comparison,
-                        comparison_result: self.previous_comparison_result.clone(),
-                    },
-                ))
-            }
-            (None, None) => None,
-        }
+        let (index, partner) = strategy::retain_pair(&self.remaining, winner, loser)?;
+        self.remaining.pairs.remove(index, partner);
+        let comparison =
+            Comparison::new(self.remaining.items[index], self.remaining.items[partner]);
+
+        self.previous_comparison.borrow_mut().replace(comparison);
+        self.previous_comparison_recorded = false;
+        Some((
+            comparison,
+            ComparisonResultTracker {
+                comparison,
+                comparison_result: self.previous_comparison_result.clone(),
+            },
+        ))
     }
 }
 
@@ -480,8 +768,6 @@ impl<'a, T: Eq + Hash + Ord> Iterator for RetainItemIterator<'a, T> {
 ///
 /// ```rust
 /// # use impaired::{Comparison, Item, Scores};
-/// use itertools::Itertools;
-///
 /// let rust = Item("Rust");
 /// let cpp = Item("C++");
 /// let java = Item("Java");
@@ -491,8 +777,8 @@ impl<'a, T: Eq + Hash + Ord> Iterator for RetainItemIterator<'a, T> {
 /// scores.track(&rust, &java);
 /// scores.track(&java, &cpp);
 ///
-/// for (item, count) in scores.iter().sorted_by(|(_, a), (_, b)| b.cmp(a)) {
-///     println!("{} ({}x)", item, count);
+/// for ranked in scores.ranked().iter() {
+///     println!("{}. {} ({}x)", ranked.rank, ranked.item, ranked.score);
 /// }
 /// ```
 ///
@@ -500,11 +786,11 @@ impl<'a, T: Eq + Hash + Ord> Iterator for RetainItemIterator<'a, T> {
 ///
 /// `Scores` automatically dereferences into a [`HashMap`](std::collections::HashMap) mapping an
 /// [`Item`](Item) to its score (a [`usize`](usize)), allowing you to interact with the results
-/// as you require.
+/// as you require. The scores can only be changed through [`Scores::track`](Scores::track), which
+/// keeps them consistent with the results the ties are broken by.
 ///
 /// ```rust
 /// # use impaired::{Comparison, Item, Scores};
-/// # use itertools::Itertools;
 /// # let rust = Item("Rust");
 /// # let cpp = Item("C++");
 /// let mut scores = Scores::new();
@@ -514,13 +800,26 @@ impl<'a, T: Eq + Hash + Ord> Iterator for RetainItemIterator<'a, T> {
 /// println!("{}", scores[&rust]);
 /// println!("{}", scores[&cpp]);
 ///
-/// // Iterate over the items and their scores
-/// for (item, count) in scores.iter().sorted_by(|(_, a), (_, b)| b.cmp(a)) {
-///     println!("{} ({}x)", item, count);
+/// // Iterate over the items and their scores, from best to worst
+/// for ranked in &scores.ranked() {
+///     println!("{} ({}x)", ranked.item, ranked.score);
 /// }
 /// ```
-#[derive(Debug, Default)]
-pub struct Scores<'a, T>(HashMap<&'a Item<T>, usize>);
+#[derive(Debug)]
+pub struct Scores<'a, T> {
+    scores: HashMap<&'a Item<T>, usize>,
+    /// The results between the items, which lists them in the order they were first tracked.
+    head_to_head: HeadToHead<'a, T>,
+}
+
+impl<'a, T> Default for Scores<'a, T> {
+    fn default() -> Self {
+        Self {
+            scores: HashMap::new(),
+            head_to_head: HeadToHead::default(),
+        }
+    }
+}
 
 impl<'a, T> Scores<'a, T>
 where
@@ -528,7 +827,7 @@ where
 {
     /// Constructs a new, empty set of scores.
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self::default()
     }
 
     /// Track the result of a single pairwise comparison.
@@ -550,31 +849,79 @@ where
     /// assert_eq!(scores[&cpp], 0);
     /// ```
     pub fn track(&mut self, winner: &'a Item<T>, loser: &'a Item<T>) {
-        self.0
-            .entry(winner)
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
-        self.0.entry(loser).or_insert(0);
+        self.register(winner);
+        self.register(loser);
+        if let Some(count) = self.scores.get_mut(winner) {
+            *count += 1;
+        }
+        self.head_to_head.track_result(winner, loser);
+    }
+
+    /// Track an item without changing its score, setting it to zero if it hasn't been tracked yet.
+    pub(crate) fn register(&mut self, item: &'a Item<T>) {
+        if !self.scores.contains_key(item) {
+            self.scores.insert(item, 0);
+            self.head_to_head.register(item);
+        }
+    }
+
+    /// How the tracked items fared against each other, listed in the order they were first
+    /// tracked.
+    pub fn head_to_head(&self) -> &HeadToHead<'a, T> {
+        &self.head_to_head
     }
 }
 
-impl<'a, T> Deref for Scores<'a, T> {
-    type Target = HashMap<&'a Item<T>, usize>;
+impl<'a, T> Scores<'a, T>
+where
+    T: Eq + Hash + Ord,
+{
+    /// The ranking of all tracked items by their scores.
+    ///
+    /// Items with equal scores are tied, they are ordered by the results between them, then by the
+    /// order in which they were first tracked. See [`Ranking`](Ranking) for details.
+    pub fn ranked(&self) -> Ranking<'a, T> {
+        let mut items: Vec<_> = self.scores.keys().copied().collect();
+        items.sort_unstable();
+        self.rank(items)
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    /// Rank the given items by their scores, see [`Scores::ranked`](Scores::ranked).
+    pub(crate) fn rank(&self, items: impl IntoIterator<Item = &'a Item<T>>) -> Ranking<'a, T> {
+        let positions: HashMap<&'a Item<T>, usize> = self
+            .head_to_head
+            .items()
+            .iter()
+            .enumerate()
+            .map(|(position, item)| (*item, position))
+            .collect();
+        let score = |item: &Item<T>| self.scores.get(item).copied().unwrap_or_default();
+
+        // Both sorts are stable: items that were never tracked keep the order they were given in.
+        let mut items: Vec<_> = items.into_iter().collect();
+        items.sort_by_key(|item| positions.get(item).copied().unwrap_or(usize::MAX));
+        items.sort_by_key(|item| cmp::Reverse(score(item)));
+
+        Ranking::new(items.into_iter().map(|item| (item, score(item) as f64))).break_ties(
+            self.head_to_head.wins().flat_map(|(winner, loser, count)| {
+                iter::repeat(ComparisonResult::new(winner, loser)).take(count)
+            }),
+        )
     }
 }
 
-impl<'a, T> DerefMut for Scores<'a, T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl<'a, T> Deref for Scores<'a, T> {
+    type Target = HashMap<&'a Item<T>, usize>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.scores
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn comparison_order_does_not_matter() {
@@ -649,6 +996,115 @@ mod test {
         }
     }
 
+    #[test]
+    fn retain_item_iterator_yields_every_comparison() {
+        for size in 2..12 {
+            let items: Vec<Item<usize>> = (0..size).map(Item).collect();
+            let comparisons = Comparisons::new(items.iter());
+            for strategy in 0..3 {
+                let mut seen = HashSet::new();
+                for (comparison, result_tracker) in comparisons.retain_item_iterator() {
+                    assert!(seen.insert(comparison));
+                    match strategy {
+                        0 => result_tracker.winner(cmp::max(comparison.left, comparison.right)),
+                        1 => result_tracker.winner(cmp::min(comparison.left, comparison.right)),
+                        _ => {}
+                    }
+                }
+                assert_eq!(seen.len(), comparisons.len());
+            }
+        }
+    }
+
+    #[test]
+    fn retain_item_iterator_stops_once_leader_is_settled() {
+        let items: Vec<Item<usize>> = (0..8).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut retain_item_iterator = comparisons
+            .retain_item_iterator()
+            .stopping_criterion(StoppingCriterion::TopSettled(1));
+
+        let mut asked = 0;
+        for (comparison, result_tracker) in retain_item_iterator.by_ref() {
+            result_tracker.winner(cmp::max(comparison.left, comparison.right));
+            asked += 1;
+        }
+
+        assert!(retain_item_iterator.is_settled());
+        assert!(asked < comparisons.len());
+        let leader = retain_item_iterator
+            .scores
+            .iter()
+            .max_by_key(|(_, score)| **score)
+            .map(|(item, _)| *item);
+        assert_eq!(leader, Some(&items[7]));
+        // Further calls must not track the last result twice.
+        assert!(retain_item_iterator.next().is_none());
+        assert_eq!(retain_item_iterator.scores[&items[7]], 7);
+    }
+
+    #[test]
+    fn retain_item_iterator_without_tracking_never_settles() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut retain_item_iterator = comparisons
+            .retain_item_iterator()
+            .stopping_criterion(StoppingCriterion::TopSettled(1));
+
+        while retain_item_iterator.next().is_some() {}
+        assert!(!retain_item_iterator.is_settled());
+    }
+
+    #[test]
+    fn top_settled_requires_every_place_to_be_settled() {
+        let items: Vec<Item<usize>> = (0..6).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let run = |places| {
+            let mut retain_item_iterator = comparisons
+                .retain_item_iterator()
+                .stopping_criterion(StoppingCriterion::TopSettled(places));
+            let mut asked = 0;
+            for (comparison, result_tracker) in retain_item_iterator.by_ref() {
+                result_tracker.winner(cmp::max(comparison.left, comparison.right));
+                asked += 1;
+            }
+            (retain_item_iterator, asked)
+        };
+
+        let (_, asked_for_leader) = run(1);
+        let (podium, asked_for_podium) = run(3);
+        assert!(asked_for_leader < asked_for_podium);
+        assert!(asked_for_podium < comparisons.len());
+
+        // Every item on the podium has more wins than any item below it could still reach.
+        let reachable = |item: &Item<usize>| {
+            let index = podium.remaining.index(item).unwrap();
+            podium.scores.get(item).copied().unwrap_or_default()
+                + podium.remaining.pairs.count(index)
+        };
+        for place in 0..3 {
+            let wins = podium.scores[&items[5 - place]];
+            assert!(items[..5 - place].iter().all(|item| reachable(item) < wins));
+        }
+    }
+
+    #[test]
+    fn top_settled_zero_never_stops_early() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let mut asked = 0;
+        for (comparison, result_tracker) in comparisons
+            .retain_item_iterator()
+            .stopping_criterion(StoppingCriterion::TopSettled(0))
+        {
+            result_tracker.winner(cmp::max(comparison.left, comparison.right));
+            asked += 1;
+        }
+        assert_eq!(asked, comparisons.len());
+    }
+
     #[test]
     #[should_panic(expected = "at least two items are required to construct the comparisons")]
     fn no_item_comparisons_construction_panics() {
@@ -0,0 +1,244 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{aggregation, ComparisonResult, Item, Scores};
+use std::{collections::HashMap, hash::Hash};
+
+struct Criterion<'a, T: Eq + Hash + Ord, C> {
+    criterion: C,
+    weight: f64,
+    results: Vec<ComparisonResult<'a, T>>,
+}
+
+/// Compare items along multiple criteria and combine them into a single, weighted ranking.
+///
+/// Every comparison is answered per criterion of your choosing `C`, e.g. value, effort and risk,
+/// where the winner is always the item that is *preferable* with regard to the criterion (e.g. the
+/// item with less effort). The results are kept per criterion, such that the weights can be changed
+/// at any time, even after all comparisons were answered, without re-voting.
+///
+/// For the combined ranking, the score of an item within a criterion is the share of its
+/// comparisons it won, which makes criteria comparable even if they were asked a different number
+/// of times. The combined score is the weighted mean of these shares, taken over the criteria the
+/// item was compared in, such that items are not penalised for criteria nobody compared them in.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Comparisons, Item, MultiCriteria};
+/// let search = Item("Search");
+/// let export = Item("Export");
+/// let dark_mode = Item("Dark mode");
+///
+/// let mut multi_criteria = MultiCriteria::new([("value", 2.0), ("effort", 1.0)]);
+/// let comparisons = Comparisons::new([&search, &export, &dark_mode]);
+/// for comparison in comparisons.iter() {
+///     // Search provides the most value, dark mode is the least effort.
+///     let valuable = if comparison.left == &search || comparison.right == &search {
+///         &search
+///     } else {
+///         &export
+///     };
+///     multi_criteria.track(&"value", valuable, comparison.other(valuable));
+///     let cheap = if comparison.left == &dark_mode || comparison.right == &dark_mode {
+///         &dark_mode
+///     } else {
+///         &export
+///     };
+///     multi_criteria.track(&"effort", cheap, comparison.other(cheap));
+/// }
+/// assert_eq!(multi_criteria.ranking()[0].0, &search);
+///
+/// // Effort turns out to matter a lot more than expected.
+/// multi_criteria.set_weight(&"effort", 5.0);
+/// assert_eq!(multi_criteria.ranking()[0].0, &dark_mode);
+/// ```
+pub struct MultiCriteria<'a, T: Eq + Hash + Ord, C> {
+    criteria: Vec<Criterion<'a, T, C>>,
+}
+
+impl<'a, T, C> MultiCriteria<'a, T, C>
+where
+    T: Eq + Hash + Ord,
+    C: PartialEq,
+{
+    /// Create a new multi-criteria ranking over the given criteria and their weights.
+    pub fn new(criteria: impl IntoIterator<Item = (C, f64)>) -> Self {
+        Self {
+            criteria: criteria
+                .into_iter()
+                .map(|(criterion, weight)| Criterion {
+                    criterion,
+                    weight,
+                    results: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    /// All criteria along with their weights, in the order they were added.
+    pub fn criteria(&self) -> Vec<(&C, f64)> {
+        self.criteria
+            .iter()
+            .map(|criterion| (&criterion.criterion, criterion.weight))
+            .collect()
+    }
+
+    /// Change the weight of a criterion, adding the criterion if it is not known yet.
+    pub fn set_weight(&mut self, criterion: &C, weight: f64)
+    where
+        C: Clone,
+    {
+        match self.find_mut(criterion) {
+            Some(known) => known.weight = weight,
+            None => self.criteria.push(Criterion {
+                criterion: criterion.clone(),
+                weight,
+                results: Vec::new(),
+            }),
+        }
+    }
+
+    /// Track the result of a comparison with regard to a single criterion.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the criterion is unknown.
+    pub fn track(&mut self, criterion: &C, winner: &'a Item<T>, loser: &'a Item<T>) {
+        self.find_mut(criterion)
+            .expect("the criterion is unknown")
+            .results
+            .push(ComparisonResult::new(winner, loser));
+    }
+
+    /// The results tracked for a criterion, in the order they were tracked.
+    pub fn results(&self, criterion: &C) -> &[ComparisonResult<'a, T>] {
+        self.criteria
+            .iter()
+            .find(|known| &known.criterion == criterion)
+            .map(|known| &known.results[..])
+            .unwrap_or_default()
+    }
+
+    /// The scores of the items with regard to a single criterion.
+    pub fn scores(&self, criterion: &C) -> Scores<'a, T> {
+        let mut scores = Scores::new();
+        for result in self.results(criterion) {
+            scores.track(result.winner, result.loser);
+        }
+        scores
+    }
+
+    /// The combined ranking according to the current weights, ordered from best to worst.
+    ///
+    /// Ties are broken by the order of the items themselves.
+    pub fn ranking(&self) -> Vec<(&'a Item<T>, f64)> {
+        // For every item, the weighted sum of its shares and the total weight of its criteria.
+        let mut combined: HashMap<&'a Item<T>, (f64, f64)> = HashMap::new();
+        for criterion in &self.criteria {
+            let mut won: HashMap<&'a Item<T>, (usize, usize)> = HashMap::new();
+            for result in &criterion.results {
+                let winner = won.entry(result.winner).or_default();
+                winner.0 += 1;
+                winner.1 += 1;
+                won.entry(result.loser).or_default().1 += 1;
+            }
+            for (item, (wins, comparisons)) in won {
+                let share = wins as f64 / comparisons as f64;
+                let (score, total_weight) = combined.entry(item).or_default();
+                *score += criterion.weight * share;
+                *total_weight += criterion.weight;
+            }
+        }
+
+        let mut ranking: Vec<_> = combined
+            .into_iter()
+            .map(|(item, (score, total_weight))| {
+                let score = if total_weight == 0.0 {
+                    0.0
+                } else {
+                    score / total_weight
+                };
+                (item, score)
+            })
+            .collect();
+        aggregation::sort_descending(&mut ranking);
+        ranking
+    }
+
+    fn find_mut(&mut self, criterion: &C) -> Option<&mut Criterion<'a, T, C>> {
+        self.criteria
+            .iter_mut()
+            .find(|known| &known.criterion == criterion)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shares_are_weighted() {
+        let items: Vec<Item<usize>> = (0..2).map(Item).collect();
+        let mut multi_criteria = MultiCriteria::new([("value", 3.0), ("effort", 1.0)]);
+        multi_criteria.track(&"value", &items[0], &items[1]);
+        multi_criteria.track(&"effort", &items[1], &items[0]);
+
+        assert_eq!(
+            multi_criteria.ranking(),
+            vec![(&items[0], 0.75), (&items[1], 0.25)]
+        );
+    }
+
+    #[test]
+    fn criteria_asked_more_often_do_not_dominate() {
+        let items: Vec<Item<usize>> = (0..2).map(Item).collect();
+        let mut multi_criteria = MultiCriteria::new([("value", 1.0), ("effort", 1.0)]);
+        for _ in 0..5 {
+            multi_criteria.track(&"value", &items[0], &items[1]);
+        }
+        multi_criteria.track(&"effort", &items[1], &items[0]);
+
+        assert_eq!(
+            multi_criteria.ranking(),
+            vec![(&items[0], 0.5), (&items[1], 0.5)]
+        );
+        assert_eq!(multi_criteria.scores(&"value")[&items[0]], 5);
+    }
+
+    #[test]
+    fn criteria_without_results_for_an_item_are_left_out() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut multi_criteria = MultiCriteria::new([("value", 1.0), ("effort", 1.0)]);
+        multi_criteria.track(&"value", &items[0], &items[1]);
+        multi_criteria.track(&"effort", &items[1], &items[2]);
+
+        assert_eq!(
+            multi_criteria.ranking(),
+            vec![(&items[0], 1.0), (&items[1], 0.5), (&items[2], 0.0)]
+        );
+    }
+
+    #[test]
+    fn weights_can_be_added_and_changed_later() {
+        let items: Vec<Item<usize>> = (0..2).map(Item).collect();
+        let mut multi_criteria = MultiCriteria::new([("value", 1.0)]);
+        multi_criteria.track(&"value", &items[0], &items[1]);
+        multi_criteria.set_weight(&"risk", 0.0);
+        multi_criteria.track(&"risk", &items[1], &items[0]);
+        assert_eq!(multi_criteria.ranking()[0], (&items[0], 1.0));
+
+        multi_criteria.set_weight(&"risk", 3.0);
+        assert_eq!(multi_criteria.ranking()[0], (&items[1], 0.75));
+        assert_eq!(
+            multi_criteria.criteria(),
+            vec![(&"value", 1.0), (&"risk", 3.0)]
+        );
+    }
+}
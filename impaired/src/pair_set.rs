@@ -0,0 +1,204 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+/// A set of unordered pairs of indices below a fixed bound, stored as a triangular bitset.
+///
+/// Every pair `(a, b)` with `a < b` occupies a single bit at position `b * (b - 1) / 2 + a`, such
+/// that the set of all pairs of `n` items requires `n * (n - 1) / 2` bits, instead of a hash set
+/// entry per pair. The number of pairs every index is part of is kept alongside, such that it can
+/// be looked up without scanning the bitset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PairSet {
+    size: usize,
+    words: Vec<u64>,
+    len: usize,
+    counts: Vec<usize>,
+}
+
+impl PairSet {
+    /// The set of all pairs of indices below `size`.
+    pub(crate) fn full(size: usize) -> Self {
+        let bits = pairs(size);
+        let mut words = vec![u64::MAX; (bits + 63) / 64];
+        if bits % 64 != 0 {
+            if let Some(last) = words.last_mut() {
+                *last = (1 << (bits % 64)) - 1;
+            }
+        }
+        Self {
+            size,
+            words,
+            len: bits,
+            counts: vec![size.saturating_sub(1); size],
+        }
+    }
+
+    /// The number of pairs in the set.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the set contains no pairs.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the set contains the pair of `a` and `b`, in any order.
+    pub(crate) fn contains(&self, a: usize, b: usize) -> bool {
+        match self.bit(a, b) {
+            Some(bit) => self.words[bit / 64] & (1 << (bit % 64)) != 0,
+            None => false,
+        }
+    }
+
+    /// Insert the pair of `a` and `b`, returning whether it was not part of the set yet.
+    ///
+    /// Pairs of an index with itself or of indices beyond the bound cannot be inserted.
+    pub(crate) fn insert(&mut self, a: usize, b: usize) -> bool {
+        let bit = match self.bit(a, b) {
+            Some(bit) => bit,
+            None => return false,
+        };
+        let word = &mut self.words[bit / 64];
+        let mask = 1 << (bit % 64);
+        let inserted = *word & mask == 0;
+        *word |= mask;
+        if inserted {
+            self.len += 1;
+            self.counts[a] += 1;
+            self.counts[b] += 1;
+        }
+        inserted
+    }
+
+    /// Remove the pair of `a` and `b`, returning whether it was part of the set.
+    pub(crate) fn remove(&mut self, a: usize, b: usize) -> bool {
+        let bit = match self.bit(a, b) {
+            Some(bit) => bit,
+            None => return false,
+        };
+        let word = &mut self.words[bit / 64];
+        let mask = 1 << (bit % 64);
+        let contained = *word & mask != 0;
+        *word &= !mask;
+        if contained {
+            self.len -= 1;
+            self.counts[a] -= 1;
+            self.counts[b] -= 1;
+        }
+        contained
+    }
+
+    /// All indices that form a pair with `a` in the set, in ascending order.
+    pub(crate) fn partners(&self, a: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.size).filter(move |b| self.contains(a, *b))
+    }
+
+    /// The smallest index that forms a pair with `a` in the set, if any.
+    pub(crate) fn partner(&self, a: usize) -> Option<usize> {
+        self.partners(a).next()
+    }
+
+    /// The number of pairs in the set that contain `a`.
+    pub(crate) fn count(&self, a: usize) -> usize {
+        self.counts.get(a).copied().unwrap_or(0)
+    }
+
+    /// Iterate over all pairs `(a, b)` with `a < b` in the set, ordered by `b`, then `a`.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| **word != 0)
+            .flat_map(|(index, word)| {
+                (0..64)
+                    .filter(move |offset| word & (1 << offset) != 0)
+                    .map(move |offset| index * 64 + offset)
+            })
+            .map(unpair)
+    }
+
+    fn bit(&self, a: usize, b: usize) -> Option<usize> {
+        let (a, b) = if a < b { (a, b) } else { (b, a) };
+        if a == b || b >= self.size {
+            None
+        } else {
+            Some(pairs(b) + a)
+        }
+    }
+}
+
+/// The number of unordered pairs of `size` items.
+fn pairs(size: usize) -> usize {
+    size * size.saturating_sub(1) / 2
+}
+
+/// The pair `(a, b)` with `a < b` stored at the given bit.
+fn unpair(bit: usize) -> (usize, usize) {
+    // Estimate `b` through the inverse of `pairs`, then correct rounding errors.
+    let mut b = ((1.0 + (1.0 + 8.0 * bit as f64).sqrt()) / 2.0) as usize;
+    while pairs(b) > bit {
+        b -= 1;
+    }
+    while pairs(b + 1) <= bit {
+        b += 1;
+    }
+    (bit - pairs(b), b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn full_set_contains_every_pair_once() {
+        for size in [0, 1, 2, 3, 11, 12, 100] {
+            let set = PairSet::full(size);
+            let pairs: Vec<_> = set.iter().collect();
+            assert_eq!(pairs.len(), size * size.saturating_sub(1) / 2);
+            assert_eq!(set.len(), pairs.len());
+            for b in 0..size {
+                for a in 0..b {
+                    assert!(set.contains(a, b) && set.contains(b, a));
+                }
+                assert!(!set.contains(b, b));
+            }
+            assert!(pairs
+                .windows(2)
+                .all(|pair| (pair[0].1, pair[0].0) < (pair[1].1, pair[1].0)));
+        }
+    }
+
+    #[test]
+    fn pairs_can_be_removed() {
+        let mut set = PairSet::full(5);
+        assert!(set.remove(3, 1));
+        assert!(!set.remove(1, 3));
+        assert!(!set.remove(2, 2));
+        assert!(!set.remove(2, 7));
+        assert_eq!(set.len(), 9);
+        assert_eq!(set.count(1), 3);
+        assert_eq!(set.partner(1), Some(0));
+
+        for b in 1..5 {
+            set.remove(0, b);
+        }
+        assert_eq!(set.partner(0), None);
+        assert_eq!(set.partner(1), Some(2));
+        assert!(set.iter().all(|(a, b)| a != 0 && (a, b) != (1, 3)));
+
+        assert!(set.insert(3, 1));
+        assert!(!set.insert(1, 3));
+        assert!(!set.insert(4, 4));
+        assert_eq!(set.len(), 6);
+        for a in 0..5 {
+            assert_eq!(set.count(a), set.partners(a).count());
+        }
+    }
+}
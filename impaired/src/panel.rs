@@ -0,0 +1,402 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{
+    aggregation, Aggregation, Comparison, ComparisonResult, Comparisons, Decision, Item, Outcome,
+    Ranking, Ranks, Scorer, Scores, Session, SessionError,
+};
+use std::{collections::HashMap, hash::Hash};
+
+/// The decision on a comparison as made by a specific voter.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Vote<'a, T: Eq + Hash + Ord, V> {
+    /// The voter that answered the comparison.
+    pub voter: V,
+    /// The decision the voter made.
+    pub decision: Decision<'a, T>,
+}
+
+impl<'a, T: Eq + Hash + Ord, V> Vote<'a, T, V> {
+    /// The result the voter chose, or `None` if the voter considered both items equal.
+    pub fn result(&self) -> Option<ComparisonResult<'a, T>> {
+        self.decision.result()
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord, V: Clone> Clone for Vote<'a, T, V> {
+    fn clone(&self) -> Self {
+        Self {
+            voter: self.voter.clone(),
+            decision: self.decision,
+        }
+    }
+}
+
+/// Collect votes on the same comparisons from multiple voters.
+///
+/// Every voter progresses independently through their own [`Session`](Session) over the
+/// comparisons, identified by a voter identity `V` of your choosing. Voters can thus also consider
+/// both items equal, skip comparisons and undo their last decision. The votes can then be evaluated
+/// per voter or aggregated into a single ranking over all voters. The
+/// [`agreement`](crate::agreement) module allows you to determine how much the voters agree with
+/// each other.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Aggregation, Comparisons, Item, Panel};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let java = Item("Java");
+///
+/// let mut panel = Panel::new(Comparisons::new([&rust, &cpp, &java]));
+/// for voter in ["alice", "bob"] {
+///     while let Some(comparison) = panel.next_comparison(&voter) {
+///         // Both voters favor Rust, then C++, then Java.
+///         let winner = if comparison.left == &rust || comparison.right == &rust {
+///             &rust
+///         } else {
+///             &cpp
+///         };
+///         panel.track(&voter, winner);
+///     }
+/// }
+///
+/// assert_eq!(panel.votes().len(), 6);
+/// assert_eq!(panel.scores(&"alice")[&rust], 2);
+///
+/// let ranking = panel.aggregate(Aggregation::SumOfWins);
+/// assert_eq!((ranking[0].item, ranking[0].score), (&rust, 4.0));
+/// ```
+pub struct Panel<'a, T: Eq + Hash + Ord, V> {
+    comparisons: Comparisons<'a, T>,
+    /// The voters in the order they were first asked a comparison.
+    voters: Vec<V>,
+    sessions: HashMap<V, Session<'a, T>>,
+    votes: Vec<Vote<'a, T, V>>,
+}
+
+impl<'a, T, V> Panel<'a, T, V>
+where
+    T: Eq + Hash + Ord,
+    V: Clone + Eq + Hash,
+{
+    /// Create a new panel in which every voter will be asked the provided comparisons.
+    pub fn new(comparisons: Comparisons<'a, T>) -> Self {
+        Self {
+            comparisons,
+            voters: Vec::new(),
+            sessions: HashMap::new(),
+            votes: Vec::new(),
+        }
+    }
+
+    /// Get the next comparison the given voter should answer.
+    ///
+    /// Voters do not need to be registered up front, calling this function for a voter for the
+    /// first time adds them to the panel. Until the comparison is answered or skipped, it is
+    /// returned again on subsequent calls. `None` is returned once the voter's session is finished.
+    pub fn next_comparison(&mut self, voter: &V) -> Option<Comparison<'a, T>> {
+        self.session_mut(voter).current_question()
+    }
+
+    /// Answer the comparison the given voter is currently asked, see
+    /// [`Session::answer`](Session::answer).
+    pub fn answer(&mut self, voter: &V, outcome: Outcome) -> Result<Decision<'a, T>, SessionError> {
+        let decision = self.session_mut(voter).answer(outcome)?;
+        self.votes.push(Vote {
+            voter: voter.clone(),
+            decision,
+        });
+        Ok(decision)
+    }
+
+    /// Track the winner of the comparison the given voter is currently asked.
+    ///
+    /// If the voter has not been asked any comparison yet, or has answered all of them, this does
+    /// nothing.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the winner is not part of the voter's current comparison.
+    pub fn track(&mut self, voter: &V, winner: &'a Item<T>) {
+        let comparison = match self.sessions.get(voter) {
+            Some(session) => session.current_question(),
+            None => None,
+        };
+        if let Some(comparison) = comparison {
+            let outcome = if comparison.left == winner {
+                Outcome::Left
+            } else if comparison.right == winner {
+                Outcome::Right
+            } else {
+                panic!("the winner has to be part of the current comparison");
+            };
+            // The voter has a current comparison, so answering cannot fail.
+            let _ = self.answer(voter, outcome);
+        }
+    }
+
+    /// Skip the comparison the given voter is currently asked, see
+    /// [`Session::skip`](Session::skip).
+    pub fn skip(&mut self, voter: &V) -> Result<Comparison<'a, T>, SessionError> {
+        self.session_mut(voter).skip()
+    }
+
+    /// Take back the last answer or skip of the given voter, along with its vote, see
+    /// [`Session::undo`](Session::undo).
+    pub fn undo(&mut self, voter: &V) -> Option<Comparison<'a, T>> {
+        let session = self.sessions.get_mut(voter)?;
+        let decisions = session.history().len();
+        let comparison = session.undo()?;
+        if session.history().len() < decisions {
+            let last_vote = self
+                .votes
+                .iter()
+                .rposition(|vote| &vote.voter == voter)
+                .expect("every decision of a voter is one of their votes");
+            self.votes.remove(last_vote);
+        }
+        Some(comparison)
+    }
+
+    /// The session of the given voter, e.g. to inspect which comparisons they skipped, or `None`
+    /// if they have not been asked any comparison yet.
+    pub fn session(&self, voter: &V) -> Option<&Session<'a, T>> {
+        self.sessions.get(voter)
+    }
+
+    /// All votes tracked so far, in the order they were tracked.
+    pub fn votes(&self) -> &[Vote<'a, T, V>] {
+        &self.votes
+    }
+
+    /// All voters that have been asked at least one comparison, in the order they were first asked.
+    pub fn voters(&self) -> Vec<&V> {
+        self.voters.iter().collect()
+    }
+
+    /// The scores according to the votes of a single voter.
+    pub fn scores(&self, voter: &V) -> Scores<'a, T> {
+        let mut scores = Scores::new();
+        for vote in self.votes.iter().filter(|vote| &vote.voter == voter) {
+            scores.observe(&vote.decision);
+        }
+        scores
+    }
+
+    /// The ranks of all items according to the votes of a single voter.
+    ///
+    /// Contrary to [`Panel::scores`](Panel::scores), this contains every item of the comparisons,
+    /// even if the voter hasn't voted on it yet. Such items are ranked as if they had no wins.
+    pub fn ranks(&self, voter: &V) -> Ranks<'a, T> {
+        Ranks::from_scores_of(&self.comparisons.items(), &self.scores(voter))
+    }
+
+    /// The scores according to the votes of all voters combined.
+    pub fn pooled_scores(&self) -> Scores<'a, T> {
+        let mut scores = Scores::new();
+        for vote in &self.votes {
+            scores.observe(&vote.decision);
+        }
+        scores
+    }
+
+    /// Aggregate the votes of all voters into a single ranking.
+    ///
+    /// Every item of the comparisons is part of the ranking, ordered from best to worst, along with
+    /// its score according to the chosen [`Aggregation`](Aggregation). Items with equal scores are
+    /// tied, and ordered by their head-to-head votes, then ascending. Mean ranks only consider
+    /// voters who cast at least one vote. See the [`aggregation`](crate::aggregation) module for
+    /// details.
+    pub fn aggregate(&self, aggregation: Aggregation) -> Ranking<'a, T> {
+        let items = self.comparisons.items();
+        let ranking = match aggregation {
+            Aggregation::MeanRank => {
+                // Voters without any vote rank all items the same, which would only drag every item
+                // towards the middle.
+                let voters: Vec<&V> = self
+                    .voters
+                    .iter()
+                    .filter(|voter| self.votes.iter().any(|vote| &vote.voter == *voter))
+                    .collect();
+                let mut rank_sums: HashMap<&'a Item<T>, f64> = HashMap::new();
+                for voter in &voters {
+                    for (item, rank) in self.ranks(voter).iter() {
+                        *rank_sums.entry(*item).or_default() += rank;
+                    }
+                }
+                let voters = voters.len().max(1) as f64;
+                let mut ranking: Vec<_> = items
+                    .into_iter()
+                    .map(|item| {
+                        (
+                            item,
+                            rank_sums.get(item).copied().unwrap_or_default() / voters,
+                        )
+                    })
+                    .collect();
+                aggregation::sort_ranking(aggregation, &mut ranking);
+                ranking
+            }
+            _ => aggregation::aggregate(
+                aggregation,
+                items,
+                self.votes.iter().filter_map(Vote::result),
+            ),
+        };
+        Ranking::new(ranking).break_ties(self.votes.iter().filter_map(Vote::result))
+    }
+
+    /// The session of the given voter, which is started if they have none yet.
+    fn session_mut(&mut self, voter: &V) -> &mut Session<'a, T> {
+        if !self.sessions.contains_key(voter) {
+            self.voters.push(voter.clone());
+            self.sessions
+                .insert(voter.clone(), Session::new(&self.comparisons));
+        }
+        self.sessions
+            .get_mut(voter)
+            .expect("the session was just started")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vote_by_preference<'a>(
+        panel: &mut Panel<'a, usize, &'static str>,
+        voter: &'static str,
+        preference: impl Fn(&Item<usize>) -> usize,
+    ) {
+        while let Some(comparison) = panel.next_comparison(&voter) {
+            let winner = if preference(comparison.left) > preference(comparison.right) {
+                comparison.left
+            } else {
+                comparison.right
+            };
+            panel.track(&voter, winner);
+        }
+    }
+
+    #[test]
+    fn voters_progress_independently() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let mut panel = Panel::new(Comparisons::new(items.iter()));
+
+        let alice_first = panel.next_comparison(&"alice").unwrap();
+        // Without tracking, the same comparison is returned again.
+        assert_eq!(panel.next_comparison(&"alice"), Some(alice_first));
+
+        vote_by_preference(&mut panel, "bob", |item| item.0);
+        assert_eq!(panel.votes().len(), 6);
+        assert_eq!(panel.next_comparison(&"alice"), Some(alice_first));
+
+        panel.track(&"alice", alice_first.left);
+        assert_eq!(panel.votes().len(), 7);
+        assert_eq!(panel.scores(&"alice")[alice_first.left], 1);
+        assert_eq!(panel.scores(&"bob")[&items[3]], 3);
+        assert_eq!(panel.voters(), vec![&"alice", &"bob"]);
+    }
+
+    #[test]
+    fn aggregations_agree_on_unanimous_votes() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let mut panel = Panel::new(Comparisons::new(items.iter()));
+        vote_by_preference(&mut panel, "alice", |item| item.0);
+        vote_by_preference(&mut panel, "bob", |item| item.0);
+
+        for aggregation in [
+            Aggregation::SumOfWins,
+            Aggregation::BradleyTerry,
+            Aggregation::MeanRank,
+            Aggregation::Copeland,
+            Aggregation::Borda,
+            Aggregation::Schulze,
+            Aggregation::Kemeny,
+        ] {
+            let ranking: Vec<_> = panel
+                .aggregate(aggregation)
+                .iter()
+                .map(|ranked| (ranked.item.0, ranked.rank))
+                .collect();
+            assert_eq!(
+                ranking,
+                vec![(3, 1), (2, 2), (1, 3), (0, 4)],
+                "{:?}",
+                aggregation
+            );
+        }
+        assert_eq!(panel.aggregate(Aggregation::MeanRank)[0].score, 1.0);
+    }
+
+    #[test]
+    fn mean_rank_averages_across_voters() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut panel = Panel::new(Comparisons::new(items.iter()));
+        vote_by_preference(&mut panel, "alice", |item| item.0);
+        vote_by_preference(&mut panel, "bob", |item| 2 - item.0);
+
+        let ranking = panel.aggregate(Aggregation::MeanRank);
+        assert!(ranking
+            .iter()
+            .all(|ranked| ranked.score == 2.0 && ranked.rank == 1));
+    }
+
+    #[test]
+    fn mean_rank_ignores_voters_without_votes() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut panel = Panel::new(Comparisons::new(items.iter()));
+        vote_by_preference(&mut panel, "alice", |item| item.0);
+        // Carol is asked, but never votes.
+        panel.next_comparison(&"carol");
+
+        let scores: Vec<_> = panel
+            .aggregate(Aggregation::MeanRank)
+            .iter()
+            .map(|ranked| (ranked.item.0, ranked.score))
+            .collect();
+        assert_eq!(scores, vec![(2, 1.0), (1, 2.0), (0, 3.0)]);
+    }
+
+    #[test]
+    fn voters_can_draw_skip_and_undo() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut panel = Panel::new(Comparisons::new(items.iter()));
+        assert_eq!(panel.undo(&"bob"), None);
+
+        let first = panel.next_comparison(&"alice").unwrap();
+        panel.answer(&"alice", Outcome::Draw).unwrap();
+        let skipped = panel.skip(&"alice").unwrap();
+        assert_eq!(panel.votes().len(), 1);
+        assert_eq!(panel.votes()[0].result(), None);
+        assert_eq!(panel.scores(&"alice")[first.left], 0);
+
+        assert_eq!(panel.undo(&"alice"), Some(skipped));
+        assert_eq!(panel.votes().len(), 1);
+        assert_eq!(panel.undo(&"alice"), Some(first));
+        assert!(panel.votes().is_empty());
+        assert_eq!(panel.next_comparison(&"alice"), Some(first));
+        assert!(panel.session(&"alice").unwrap().history().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "the winner has to be part of the current comparison")]
+    fn winners_have_to_be_part_of_the_comparison() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut panel = Panel::new(Comparisons::new(items.iter()));
+        let comparison = panel.next_comparison(&"alice").unwrap();
+        let outsider = items
+            .iter()
+            .find(|item| *item != comparison.left && *item != comparison.right)
+            .unwrap();
+        panel.track(&"alice", outsider);
+    }
+}
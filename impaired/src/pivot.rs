@@ -0,0 +1,314 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{Comparison, ComparisonResult, ComparisonResultTracker, Comparisons, Item};
+use std::{cell::RefCell, hash::Hash, mem, rc::Rc};
+
+/// A group of items whose order among each other has not been determined (yet).
+#[derive(Debug)]
+struct Group<'a, T> {
+    items: Vec<&'a Item<T>>,
+    depth: usize,
+}
+
+/// A group that is currently being partitioned around a pivot item.
+#[derive(Debug)]
+struct Partition<'a, T> {
+    position: usize,
+    depth: usize,
+    pivot: &'a Item<T>,
+    pending: Vec<&'a Item<T>>,
+    better: Vec<&'a Item<T>>,
+    worse: Vec<&'a Item<T>>,
+}
+
+/// An iterator comparing items against a pivot item, partitioning them recursively like quicksort.
+///
+/// Every comparison contains the current pivot item, such that voters answer "is this item more
+/// important than our reference item?" over and over, which is a lot faster to answer than
+/// comparisons between random pairs. Once every item of a group has been compared against the
+/// pivot, the group is split into the items that beat the pivot, the pivot itself, and the items
+/// that lost against it, and the groups are partitioned further, best group first.
+///
+/// The partitioning can be limited to a maximum depth through
+/// [`PivotIterator::max_depth`](PivotIterator::max_depth), in which case
+/// [`PivotIterator::partial_order`](PivotIterator::partial_order) yields ordered groups of items
+/// whose order within every group is left undetermined.
+///
+/// Items whose winner is not tracked, or whose comparison with the pivot is not part of the
+/// comparisons (e.g. because a [`Prior`](crate::Prior) established it), are considered to have
+/// lost against the pivot.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Comparisons, Item};
+/// let items: Vec<Item<usize>> = (0..8).map(Item).collect();
+/// let comparisons = Comparisons::new(items.iter());
+///
+/// let mut iterator = comparisons.pivot_iterator().max_depth(1);
+/// for (comparison, result_tracker) in iterator.by_ref() {
+///     // The bigger number always wins.
+///     result_tracker.winner(std::cmp::max(comparison.left, comparison.right));
+/// }
+///
+/// // A single partition leaves the items better and worse than the pivot unordered.
+/// let groups: Vec<Vec<usize>> = iterator
+///     .partial_order()
+///     .into_iter()
+///     .map(|group| group.into_iter().map(|item| item.0).collect())
+///     .collect();
+/// assert_eq!(groups, vec![vec![5, 6, 7], vec![4], vec![0, 1, 2, 3]]);
+/// ```
+pub struct PivotIterator<'a, T: Eq + Hash + Ord> {
+    comparisons: Comparisons<'a, T>,
+    groups: Vec<Group<'a, T>>,
+    partition: Option<Partition<'a, T>>,
+    max_depth: Option<usize>,
+    previous_comparison: Option<Comparison<'a, T>>,
+    previous_comparison_recorded: bool,
+    previous_comparison_result: Rc<RefCell<Option<ComparisonResult<'a, T>>>>,
+}
+
+impl<'a, T: Eq + Hash + Ord> PivotIterator<'a, T> {
+    pub(crate) fn new(input: &Comparisons<'a, T>) -> Self {
+        Self {
+            comparisons: input.clone(),
+            groups: vec![Group {
+                items: input.items(),
+                depth: 0,
+            }],
+            partition: None,
+            max_depth: None,
+            previous_comparison: None,
+            previous_comparison_recorded: false,
+            previous_comparison_result: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Stop partitioning groups after the given number of levels, leaving the order within the
+    /// resulting groups undetermined.
+    ///
+    /// A depth of `1` only partitions all items around a single pivot.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Track the winner of the current comparison.
+    ///
+    /// This fulfills the same purpose as
+    /// [`ComparisonResultTracker::winner`](ComparisonResultTracker::winner), see
+    /// [`RetainItemIterator::winner`](crate::RetainItemIterator::winner) for when to use which.
+    pub fn winner(&mut self, winner: &'a Item<T>) {
+        if let Some(previous_comparison) = self.previous_comparison {
+            let loser = previous_comparison.other(winner);
+            self.previous_comparison_result
+                .borrow_mut()
+                .replace(ComparisonResult {
+                    comparison: previous_comparison,
+                    winner,
+                    loser,
+                });
+        }
+    }
+
+    /// The order of the items determined so far, as groups ordered from best to worst.
+    ///
+    /// The order of the items within a group is undetermined; they are sorted by the items
+    /// themselves. Once the iterator is exhausted without a maximum depth, every group contains a
+    /// single item.
+    pub fn partial_order(&self) -> Vec<Vec<&'a Item<T>>> {
+        let mut groups: Vec<Vec<&'a Item<T>>> = Vec::with_capacity(self.groups.len() + 2);
+        for (position, group) in self.groups.iter().enumerate() {
+            match &self.partition {
+                Some(partition) if partition.position == position => {
+                    let mut undetermined = partition.pending.clone();
+                    undetermined.extend(&partition.better);
+                    undetermined.extend(&partition.worse);
+                    undetermined.push(partition.pivot);
+                    groups.push(undetermined);
+                }
+                _ => groups.push(group.items.clone()),
+            }
+        }
+        for group in &mut groups {
+            group.sort_unstable();
+        }
+        groups
+    }
+
+    fn record_previous_comparison_result(&mut self) {
+        if self.previous_comparison_recorded {
+            return;
+        }
+        self.previous_comparison_recorded = true;
+
+        let (partition, previous_comparison) =
+            match (self.partition.as_mut(), self.previous_comparison) {
+                (Some(partition), Some(previous_comparison)) => (partition, previous_comparison),
+                _ => return,
+            };
+        let item = previous_comparison.other(partition.pivot);
+        match *self.previous_comparison_result.borrow() {
+            // The result might still be the one of an earlier comparison if the winner of the
+            // previous comparison wasn't tracked.
+            Some(result) if result.comparison == previous_comparison && result.winner == item => {
+                partition.better.push(item)
+            }
+            _ => partition.worse.push(item),
+        }
+    }
+
+    /// Replace the group of a fully compared partition by its parts.
+    fn finish_partition(&mut self) {
+        let partition = match self.partition.take() {
+            Some(partition) => partition,
+            None => return,
+        };
+        let depth = partition.depth + 1;
+        let parts = [partition.better, vec![partition.pivot], partition.worse]
+            .into_iter()
+            .filter(|items| !items.is_empty())
+            .map(|items| Group { items, depth });
+        self.groups
+            .splice(partition.position..=partition.position, parts);
+    }
+
+    /// Start partitioning the best group that can still be partitioned, if any.
+    fn start_partition(&mut self) -> bool {
+        let max_depth = self.max_depth;
+        let position = match self.groups.iter().position(|group| {
+            group.items.len() > 1 && max_depth.map_or(true, |max_depth| group.depth < max_depth)
+        }) {
+            Some(position) => position,
+            None => return false,
+        };
+
+        let group = &mut self.groups[position];
+        let mut items = mem::take(&mut group.items);
+        items.sort_unstable();
+        let pivot = items.remove(items.len() / 2);
+        // Pending items are popped from the back, so reverse them to compare in ascending order.
+        items.reverse();
+        group.items = items.clone();
+        group.items.push(pivot);
+        self.partition = Some(Partition {
+            position,
+            depth: group.depth,
+            pivot,
+            pending: items,
+            better: Vec::new(),
+            worse: Vec::new(),
+        });
+        true
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Iterator for PivotIterator<'a, T> {
+    type Item = (Comparison<'a, T>, ComparisonResultTracker<'a, T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.record_previous_comparison_result();
+
+        loop {
+            let partition = match self.partition.as_mut() {
+                Some(partition) => partition,
+                None => {
+                    if !self.start_partition() {
+                        return None;
+                    }
+                    continue;
+                }
+            };
+
+            let item = match partition.pending.pop() {
+                Some(item) => item,
+                None => {
+                    self.finish_partition();
+                    continue;
+                }
+            };
+            let comparison = Comparison::new(partition.pivot, item);
+            if !self.comparisons.contains(&comparison) {
+                partition.worse.push(item);
+                continue;
+            }
+
+            self.previous_comparison.replace(comparison);
+            self.previous_comparison_recorded = false;
+            return Some((
+                comparison,
+                ComparisonResultTracker {
+                    comparison,
+                    comparison_result: self.previous_comparison_result.clone(),
+                },
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_comparison_contains_the_pivot() {
+        let items: Vec<Item<usize>> = (0..5).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let mut iterator = comparisons.pivot_iterator().max_depth(1);
+        let mut asked = 0;
+        while let Some((comparison, _)) = iterator.next() {
+            assert!(comparison.left == &items[2] || comparison.right == &items[2]);
+            iterator.winner(&items[2]);
+            asked += 1;
+        }
+        assert_eq!(asked, 4);
+    }
+
+    #[test]
+    fn exhausting_the_iterator_yields_a_total_order() {
+        let items: Vec<Item<usize>> = (0..10).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let mut iterator = comparisons.pivot_iterator();
+        let mut asked = 0;
+        for (comparison, result_tracker) in iterator.by_ref() {
+            // The smaller number always wins.
+            result_tracker.winner(std::cmp::min(comparison.left, comparison.right));
+            asked += 1;
+        }
+        assert!(asked < comparisons.len());
+
+        let order: Vec<Vec<usize>> = iterator
+            .partial_order()
+            .into_iter()
+            .map(|group| group.into_iter().map(|item| item.0).collect())
+            .collect();
+        assert_eq!(order, (0..10).map(|item| vec![item]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn untracked_items_lose_against_the_pivot() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let mut iterator = comparisons.pivot_iterator().max_depth(1);
+        assert_eq!(
+            iterator.partial_order(),
+            vec![items.iter().collect::<Vec<_>>()]
+        );
+        while iterator.next().is_some() {}
+        assert_eq!(
+            iterator.partial_order(),
+            vec![vec![&items[1]], vec![&items[0], &items[2]]]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{Comparison, ComparisonResult, Comparisons, EventLog, Item};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// Knowledge about the order of items that is available before comparing them, e.g. from a previous
+/// session.
+///
+/// A prior refers to items by their values rather than by reference, such that it can stem from a
+/// different set of items, e.g. last sprint's backlog. Values the current comparisons do not
+/// contain are ignored.
+///
+/// See [`Comparisons::warm_start`](Comparisons::warm_start) for how a prior is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prior<T> {
+    /// An existing ordering of items, from best to worst.
+    ///
+    /// The relative order of every two items within the ordering is considered established.
+    Ordering(Vec<T>),
+    /// Existing ratings of items, where a higher rating is better.
+    ///
+    /// The order of two rated items is considered established if their ratings differ by at least
+    /// `margin`. Items whose ratings are closer than that are compared again.
+    Ratings {
+        /// The items and their ratings.
+        ratings: Vec<(T, f64)>,
+        /// The difference in ratings from which on the order of two items is established.
+        margin: f64,
+    },
+    /// The results of previous comparisons, as `(winner, loser)`, e.g. imported from an
+    /// [`EventLog`](EventLog).
+    ///
+    /// Every pair of items that was compared before is considered established, and all of its
+    /// results are carried over.
+    Results(Vec<(T, T)>),
+}
+
+impl<T: Clone> From<&EventLog<T>> for Prior<T> {
+    fn from(event_log: &EventLog<T>) -> Self {
+        Self::Results(
+            event_log
+                .results()
+                .map(|(winner, loser)| (winner.clone(), loser.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// The comparisons that remain to be asked after applying a [`Prior`](Prior), along with the
+/// results the prior established.
+///
+/// See [`Comparisons::warm_start`](Comparisons::warm_start).
+#[derive(Debug)]
+pub struct WarmStart<'a, T: Eq + Hash + Ord> {
+    /// The comparisons whose result is not established by the prior.
+    pub comparisons: Comparisons<'a, T>,
+    /// The results the prior established, which should be tracked like any other result.
+    pub established: Vec<ComparisonResult<'a, T>>,
+}
+
+impl<'a, T: Eq + Hash + Ord> Comparisons<'a, T> {
+    /// Apply a prior to the comparisons, skipping every comparison whose result the prior already
+    /// establishes.
+    ///
+    /// This allows re-ranking a list of items that was mostly ranked before without re-asking every
+    /// comparison. Track the [established results](WarmStart::established) in your
+    /// [`Scores`](crate::Scores) (or any other model) before iterating the
+    /// [remaining comparisons](WarmStart::comparisons), such that the final ranking takes both into
+    /// account, or start a [`Session`](crate::Session) through
+    /// [`Session::warm_start`](crate::Session::warm_start), which does so.
+    ///
+    /// ```rust
+    /// # use impaired::{Comparisons, Item, Prior, Scores};
+    /// let rust = Item("Rust");
+    /// let cpp = Item("C++");
+    /// let java = Item("Java");
+    /// let zig = Item("Zig");
+    ///
+    /// // Last time, Rust was ranked above C++, which was ranked above Java. Zig is new.
+    /// let comparisons = Comparisons::new([&rust, &cpp, &java, &zig]);
+    /// let warm_start = comparisons.warm_start(&Prior::Ordering(vec!["Rust", "C++", "Java"]));
+    /// assert_eq!(warm_start.established.len(), 3);
+    /// assert_eq!(warm_start.comparisons.len(), 3);
+    ///
+    /// let mut scores = Scores::new();
+    /// for result in &warm_start.established {
+    ///     scores.track(result.winner, result.loser);
+    /// }
+    /// for (comparison, result_tracker) in warm_start.comparisons.retain_item_iterator() {
+    ///     // Only the comparisons against Zig are left.
+    ///     assert!(comparison.left == &zig || comparison.right == &zig);
+    ///     scores.track(comparison.other(&zig), &zig);
+    ///     result_tracker.winner(comparison.other(&zig));
+    /// }
+    /// assert_eq!(scores[&rust], 3);
+    /// ```
+    pub fn warm_start(&self, prior: &Prior<T>) -> WarmStart<'a, T> {
+        let items_by_value: HashMap<&T, &'a Item<T>> = self
+            .items()
+            .into_iter()
+            .map(|item| (&item.0, item))
+            .collect();
+
+        let mut established: Vec<ComparisonResult<'a, T>> = Vec::new();
+        match prior {
+            Prior::Ordering(ordering) => {
+                let ordering: Vec<_> = ordering
+                    .iter()
+                    .filter_map(|value| items_by_value.get(value).copied())
+                    .collect();
+                for (position, winner) in ordering.iter().enumerate() {
+                    for loser in &ordering[position + 1..] {
+                        if winner != loser {
+                            established.push(ComparisonResult::new(winner, loser));
+                        }
+                    }
+                }
+            }
+            Prior::Ratings { ratings, margin } => {
+                let ratings: Vec<_> = ratings
+                    .iter()
+                    .filter_map(|(value, rating)| {
+                        items_by_value.get(value).map(|item| (*item, *rating))
+                    })
+                    .collect();
+                for (position, (a, a_rating)) in ratings.iter().enumerate() {
+                    for (b, b_rating) in &ratings[position + 1..] {
+                        if a == b || (a_rating - b_rating).abs() < *margin {
+                            continue;
+                        }
+                        established.push(if a_rating > b_rating {
+                            ComparisonResult::new(a, b)
+                        } else {
+                            ComparisonResult::new(b, a)
+                        });
+                    }
+                }
+            }
+            Prior::Results(results) => {
+                established.extend(results.iter().filter_map(|(winner, loser)| {
+                    match (items_by_value.get(winner), items_by_value.get(loser)) {
+                        (Some(winner), Some(loser)) if winner != loser => {
+                            Some(ComparisonResult::new(winner, loser))
+                        }
+                        _ => None,
+                    }
+                }));
+            }
+        }
+
+        let established_comparisons: HashSet<Comparison<'a, T>> =
+            established.iter().map(|result| result.comparison).collect();
+        let mut comparisons = self.clone();
+        comparisons.retain(|comparison| !established_comparisons.contains(comparison));
+        WarmStart {
+            comparisons,
+            established,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ratings_establish_distant_pairs_only() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let warm_start = comparisons.warm_start(&Prior::Ratings {
+            ratings: vec![(0, 1.0), (1, 1.5), (2, 3.0)],
+            margin: 1.0,
+        });
+
+        assert_eq!(
+            warm_start.established,
+            vec![
+                ComparisonResult::new(&items[2], &items[0]),
+                ComparisonResult::new(&items[2], &items[1]),
+            ]
+        );
+        assert_eq!(warm_start.comparisons.len(), 1);
+        assert!(warm_start
+            .comparisons
+            .contains(&Comparison::new(&items[0], &items[1])));
+    }
+
+    #[test]
+    fn results_of_unknown_items_are_ignored() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let warm_start =
+            comparisons.warm_start(&Prior::Results(vec![(0, 1), (1, 0), (0, 1), (0, 42)]));
+
+        assert_eq!(warm_start.established.len(), 3);
+        assert_eq!(warm_start.comparisons.len(), 2);
+    }
+
+    #[test]
+    fn event_logs_provide_results() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let event_log: EventLog<usize> =
+            EventLog::read_from("item\t0\nitem\t1\nresult\t1\t0\n".as_bytes()).unwrap();
+        let warm_start = comparisons.warm_start(&Prior::from(&event_log));
+
+        assert_eq!(
+            warm_start.established,
+            vec![ComparisonResult::new(&items[1], &items[0])]
+        );
+    }
+
+    #[test]
+    fn fully_established_comparisons_can_be_iterated() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let warm_start = comparisons.warm_start(&Prior::Ordering(vec![2, 1, 0]));
+
+        assert!(warm_start.comparisons.is_empty());
+        assert!(warm_start
+            .comparisons
+            .retain_item_iterator()
+            .next()
+            .is_none());
+    }
+}
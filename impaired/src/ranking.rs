@@ -0,0 +1,260 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{ComparisonResult, Item};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::{Deref, Range},
+};
+
+/// An item of a [`Ranking`](Ranking) along with its score and ranks.
+#[derive(Debug, PartialEq)]
+pub struct Ranked<'a, T> {
+    /// The ranked item.
+    pub item: &'a Item<T>,
+    /// The score of the item, according to whichever model produced the ranking.
+    pub score: f64,
+    /// The standard competition rank of the item, starting at `1`.
+    ///
+    /// Tied items share the same rank, leaving a gap after them: four items of which the middle two
+    /// are tied are ranked `1`, `2`, `2`, `4`.
+    pub rank: usize,
+    /// The dense rank of the item, starting at `1`.
+    ///
+    /// Tied items share the same rank, without leaving a gap after them: four items of which the
+    /// middle two are tied are ranked `1`, `2`, `2`, `3`.
+    pub dense_rank: usize,
+}
+
+impl<'a, T> Clone for Ranked<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Ranked<'a, T> {}
+
+/// Items ordered from best to worst, with ties made explicit.
+///
+/// Items with equal scores are tied: they share their [`rank`](Ranked::rank) and
+/// [`dense_rank`](Ranked::dense_rank), and form a [tie group](Ranking::tie_groups). To still list
+/// tied items in a deterministic order, they are ordered by their head-to-head results among each
+/// other (see [`Ranking::break_ties`](Ranking::break_ties)), then by the order they were provided
+/// in, which for [`Scores`](crate::Scores) is the order in which they were first tracked.
+///
+/// `Ranking` dereferences into a slice of [`Ranked`](Ranked) items.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Item, Scores};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let java = Item("Java");
+/// let go = Item("Go");
+///
+/// let mut scores = Scores::new();
+/// scores.track(&rust, &cpp);
+/// scores.track(&rust, &java);
+/// scores.track(&go, &java);
+/// scores.track(&cpp, &go);
+///
+/// let ranking = scores.ranked();
+/// let ranks: Vec<_> = ranking
+///     .iter()
+///     .map(|ranked| (ranked.item.0, ranked.rank, ranked.dense_rank))
+///     .collect();
+/// // C++ and Go are tied, C++ is listed first as it beat Go.
+/// assert_eq!(
+///     ranks,
+///     vec![("Rust", 1, 1), ("C++", 2, 2), ("Go", 2, 2), ("Java", 4, 3)]
+/// );
+/// assert_eq!(ranking.tie_groups().nth(1).map(|group| group.len()), Some(2));
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Ranking<'a, T>(Vec<Ranked<'a, T>>);
+
+impl<'a, T> Clone for Ranking<'a, T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Ranking<'a, T> {
+    /// Create a ranking from items and their scores, ordered from best to worst.
+    ///
+    /// Consecutive items with equal scores are tied and keep the order they were provided in.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if an item appears more than once.
+    pub fn new(ranking: impl IntoIterator<Item = (&'a Item<T>, f64)>) -> Self {
+        Self::with_ties_if(ranking, |_, _| true)
+    }
+
+    /// Create a ranking like [`Ranking::new`](Ranking::new), in which consecutive items with equal
+    /// scores are only tied if `tied` returns `true` for them, e.g. because they belong to the
+    /// same bucket.
+    pub(crate) fn with_ties_if(
+        ranking: impl IntoIterator<Item = (&'a Item<T>, f64)>,
+        mut tied: impl FnMut(&'a Item<T>, &'a Item<T>) -> bool,
+    ) -> Self {
+        let mut seen = HashSet::new();
+        let mut ranked: Vec<Ranked<'a, T>> = Vec::new();
+        for (item, score) in ranking {
+            assert!(seen.insert(item), "every item can only be ranked once");
+            let (rank, dense_rank) = match ranked.last() {
+                Some(previous) if previous.score == score && tied(previous.item, item) => {
+                    (previous.rank, previous.dense_rank)
+                }
+                Some(previous) => (ranked.len() + 1, previous.dense_rank + 1),
+                None => (1, 1),
+            };
+            ranked.push(Ranked {
+                item,
+                score,
+                rank,
+                dense_rank,
+            });
+        }
+        Self(ranked)
+    }
+
+    /// Order the items within every tie group by their head-to-head results against each other.
+    ///
+    /// Within a group, items are ordered by the number of results in which they won against another
+    /// item of the same group, most wins first. Items with the same number of head-to-head wins keep
+    /// their order. The ranks are not affected, tied items stay tied.
+    pub fn break_ties(
+        mut self,
+        results: impl IntoIterator<Item = ComparisonResult<'a, T>>,
+    ) -> Self {
+        let mut head_to_head: HashMap<(&'a Item<T>, &'a Item<T>), usize> = HashMap::new();
+        for result in results {
+            *head_to_head
+                .entry((result.winner, result.loser))
+                .or_default() += 1;
+        }
+
+        for range in self.tie_ranges() {
+            let group = &mut self.0[range];
+            let wins: HashMap<&'a Item<T>, usize> = group
+                .iter()
+                .map(|a| {
+                    let wins = group
+                        .iter()
+                        .filter_map(|b| head_to_head.get(&(a.item, b.item)))
+                        .sum();
+                    (a.item, wins)
+                })
+                .collect();
+            group.sort_by_key(|ranked| Reverse(wins[ranked.item]));
+        }
+        self
+    }
+
+    /// The ranked entry of the given item, if it is part of the ranking.
+    pub fn get(&self, item: &Item<T>) -> Option<&Ranked<'a, T>> {
+        self.0.iter().find(|ranked| ranked.item == item)
+    }
+
+    /// The groups of tied items, from best to worst.
+    ///
+    /// Items that are not tied with any other item form a group of their own.
+    pub fn tie_groups(&self) -> impl Iterator<Item = &[Ranked<'a, T>]> + '_ {
+        self.tie_ranges()
+            .into_iter()
+            .map(move |range| &self.0[range])
+    }
+
+    /// The items from best to worst, along with their scores.
+    pub fn scores(&self) -> Vec<(&'a Item<T>, f64)> {
+        self.0
+            .iter()
+            .map(|ranked| (ranked.item, ranked.score))
+            .collect()
+    }
+
+    fn tie_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (index, ranked) in self.0.iter().enumerate() {
+            match ranges.last_mut() {
+                Some(range) if self.0[range.start].rank == ranked.rank => range.end = index + 1,
+                _ => ranges.push(index..index + 1),
+            }
+        }
+        ranges
+    }
+}
+
+impl<'a, T> Deref for Ranking<'a, T> {
+    type Target = [Ranked<'a, T>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'r, 'a, T> IntoIterator for &'r Ranking<'a, T> {
+    type Item = &'r Ranked<'a, T>;
+    type IntoIter = std::slice::Iter<'r, Ranked<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ties_share_ranks() {
+        let items: Vec<Item<usize>> = (0..6).map(Item).collect();
+        let ranking = Ranking::new([
+            (&items[0], 5.0),
+            (&items[1], 3.0),
+            (&items[2], 3.0),
+            (&items[3], 3.0),
+            (&items[4], 1.0),
+            (&items[5], 0.0),
+        ]);
+
+        let ranks: Vec<_> = ranking.iter().map(|ranked| ranked.rank).collect();
+        assert_eq!(ranks, vec![1, 2, 2, 2, 5, 6]);
+        let dense_ranks: Vec<_> = ranking.iter().map(|ranked| ranked.dense_rank).collect();
+        assert_eq!(dense_ranks, vec![1, 2, 2, 2, 3, 4]);
+        let groups: Vec<_> = ranking.tie_groups().map(|group| group.len()).collect();
+        assert_eq!(groups, vec![1, 3, 1, 1]);
+        assert_eq!(ranking.get(&items[3]).map(|ranked| ranked.rank), Some(2));
+    }
+
+    #[test]
+    fn ties_are_broken_by_head_to_head_then_order() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let ranking = Ranking::new([
+            (&items[3], 1.0),
+            (&items[0], 1.0),
+            (&items[2], 1.0),
+            (&items[1], 0.0),
+        ])
+        .break_ties([
+            ComparisonResult::new(&items[2], &items[0]),
+            // Results against items outside the group do not count.
+            ComparisonResult::new(&items[0], &items[1]),
+            ComparisonResult::new(&items[0], &items[1]),
+        ]);
+
+        let order: Vec<_> = ranking.iter().map(|ranked| ranked.item.0).collect();
+        assert_eq!(order, vec![2, 3, 0, 1]);
+        assert!(ranking[..3].iter().all(|ranked| ranked.rank == 1));
+    }
+}
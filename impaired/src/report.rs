@@ -0,0 +1,384 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{
+    svg::{self, escape, format_score},
+    BradleyTerry, Decision, HeadToHead, Outcome, Ranking, Scorer, Session, Strategy,
+};
+use std::{
+    fmt::Display,
+    hash::Hash,
+    io::{self, Write},
+};
+
+/// How consistent the decisions of a session are with each other and with the final ranking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Consistency {
+    /// The number of decisions made, including draws.
+    pub decisions: usize,
+    /// The number of decisions that were draws.
+    pub draws: usize,
+    /// The number of decisions in which the winner is ranked below the loser.
+    pub upsets: usize,
+    /// The number of triples of items whose majority preferences form a cycle, e.g. `a` beats `b`,
+    /// `b` beats `c` and `c` beats `a`.
+    pub circular_triads: usize,
+}
+
+impl Consistency {
+    /// The share of decisions that were not draws and agree with the final ranking, between `0` and
+    /// `1`, or `None` if every decision was a draw.
+    pub fn agreement(&self) -> Option<f64> {
+        let decided = self.decisions - self.draws;
+        if decided == 0 {
+            None
+        } else {
+            Some((decided - self.upsets) as f64 / decided as f64)
+        }
+    }
+}
+
+/// A self-contained report of a [`Session`](Session), as Markdown or HTML.
+///
+/// The report lists the ranking along with the score of every item and how often it was compared,
+/// followed by the [consistency](Consistency) of the decisions. The HTML report additionally embeds
+/// the [win-probability heatmap](svg::heatmap) of the items. Every single decision can be listed
+/// as well, see [`Report::decisions`](Report::decisions).
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Comparisons, Item, Outcome, Report, Session};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let comparisons = Comparisons::new([&rust, &cpp]);
+///
+/// let mut session = Session::new(&comparisons);
+/// // C++ is asked about on the left, Rust on the right.
+/// session.answer(Outcome::Right)?;
+///
+/// let mut markdown = Vec::new();
+/// Report::new(&session)
+///     .title("Languages")
+///     .decisions(true)
+///     .write_markdown(&mut markdown)?;
+/// let markdown = String::from_utf8(markdown).unwrap();
+/// assert!(markdown.starts_with("# Languages\n"));
+/// assert!(markdown.contains("| 1 | Rust | 1 | 1 | 1 | 0 | 0 |"));
+/// assert!(markdown.contains("1. **Rust** beat C++"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Report<'a, T: Eq + Hash + Ord> {
+    title: String,
+    include_decisions: bool,
+    ranking: Ranking<'a, T>,
+    head_to_head: HeadToHead<'a, T>,
+    history: Vec<Decision<'a, T>>,
+}
+
+impl<'a, T: Display + Eq + Hash + Ord> Report<'a, T> {
+    /// Create a report of the current state of the given session.
+    ///
+    /// The report is usually created once the session is finished, but works for unfinished
+    /// sessions just as well.
+    pub fn new<S: Strategy<'a, T>, R: Scorer<'a, T>>(session: &Session<'a, T, S, R>) -> Self {
+        let ranking = session.ranking();
+        Self {
+            title: "Ranking".to_owned(),
+            include_decisions: false,
+            head_to_head: session.head_to_head_ordered_by(&ranking),
+            ranking,
+            history: session.history().to_vec(),
+        }
+    }
+
+    /// Set the title of the report, `Ranking` by default.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set whether every decision should be listed, in the order they were made. They are left out
+    /// by default.
+    pub fn decisions(mut self, include_decisions: bool) -> Self {
+        self.include_decisions = include_decisions;
+        self
+    }
+
+    /// The consistency of the decisions of the session.
+    pub fn consistency(&self) -> Consistency {
+        let rank = |item| self.ranking.get(item).map(|ranked| ranked.rank);
+        let upsets = self
+            .history
+            .iter()
+            .filter_map(Decision::result)
+            .filter(|result| rank(result.winner) > rank(result.loser))
+            .count();
+
+        let items = self.head_to_head.items();
+        let beats = |a: usize, b: usize| {
+            let record = self.head_to_head.record(items[a], items[b]);
+            record.wins > record.losses
+        };
+        let mut circular_triads = 0;
+        for a in 0..items.len() {
+            for b in a + 1..items.len() {
+                for c in b + 1..items.len() {
+                    if (beats(a, b) && beats(b, c) && beats(c, a))
+                        || (beats(a, c) && beats(c, b) && beats(b, a))
+                    {
+                        circular_triads += 1;
+                    }
+                }
+            }
+        }
+
+        Consistency {
+            decisions: self.history.len(),
+            draws: self
+                .history
+                .iter()
+                .filter(|decision| decision.outcome == Outcome::Draw)
+                .count(),
+            upsets,
+            circular_triads,
+        }
+    }
+
+    /// Write the report as Markdown.
+    pub fn write_markdown(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "# {}", markdown(&self.title))?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "| Rank | Item | Score | Comparisons | Wins | Losses | Draws |"
+        )?;
+        writeln!(writer, "| ---: | --- | ---: | ---: | ---: | ---: | ---: |")?;
+        for ranked in &self.ranking {
+            let record = self.head_to_head.total(ranked.item);
+            writeln!(
+                writer,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                ranked.rank,
+                markdown(ranked.item),
+                format_score(ranked.score),
+                record.total(),
+                record.wins,
+                record.losses,
+                record.draws
+            )?;
+        }
+        writeln!(writer)?;
+
+        writeln!(writer, "## Consistency")?;
+        writeln!(writer)?;
+        for (label, value) in self.consistency_rows() {
+            writeln!(writer, "- {}: {}", label, value)?;
+        }
+
+        if self.include_decisions {
+            writeln!(writer)?;
+            writeln!(writer, "## Decisions")?;
+            writeln!(writer)?;
+            for (index, decision) in self.history.iter().enumerate() {
+                let (left, right) = (
+                    markdown(decision.comparison.left),
+                    markdown(decision.comparison.right),
+                );
+                match decision.outcome {
+                    Outcome::Left => {
+                        writeln!(writer, "{}. **{}** beat {}", index + 1, left, right)?
+                    }
+                    Outcome::Right => {
+                        writeln!(writer, "{}. **{}** beat {}", index + 1, right, left)?
+                    }
+                    Outcome::Draw => {
+                        writeln!(writer, "{}. {} and {} drew", index + 1, left, right)?
+                    }
+                }
+            }
+        }
+        writer.flush()
+    }
+
+    /// Write the report as a standalone HTML document, without any external resources.
+    pub fn write_html(&self, mut writer: impl Write) -> io::Result<()> {
+        let title = escape(&self.title);
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html lang=\"en\">")?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, "<meta charset=\"utf-8\">")?;
+        writeln!(writer, "<title>{}</title>", title)?;
+        writeln!(
+            writer,
+            "<style>body {{ font-family: sans-serif; margin: 2em; }} \
+             table {{ border-collapse: collapse; }} \
+             th, td {{ border: 1px solid #cccccc; padding: 0.25em 0.5em; }} \
+             td.number {{ text-align: right; }}</style>"
+        )?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, "<h1>{}</h1>", title)?;
+        writeln!(writer, "<table>")?;
+        writeln!(
+            writer,
+            "<tr><th>Rank</th><th>Item</th><th>Score</th><th>Comparisons</th>\
+             <th>Wins</th><th>Losses</th><th>Draws</th></tr>"
+        )?;
+        for ranked in &self.ranking {
+            let record = self.head_to_head.total(ranked.item);
+            writeln!(
+                writer,
+                "<tr><td class=\"number\">{}</td><td>{}</td><td class=\"number\">{}</td>\
+                 <td class=\"number\">{}</td><td class=\"number\">{}</td>\
+                 <td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+                ranked.rank,
+                escape(ranked.item),
+                format_score(ranked.score),
+                record.total(),
+                record.wins,
+                record.losses,
+                record.draws
+            )?;
+        }
+        writeln!(writer, "</table>")?;
+
+        writeln!(writer, "<h2>Consistency</h2>")?;
+        writeln!(writer, "<ul>")?;
+        for (label, value) in self.consistency_rows() {
+            writeln!(writer, "<li>{}: {}</li>", label, value)?;
+        }
+        writeln!(writer, "</ul>")?;
+
+        writeln!(writer, "<h2>Win probabilities</h2>")?;
+        let items: Vec<_> = self.ranking.iter().map(|ranked| ranked.item).collect();
+        let model = BradleyTerry::fit(
+            items.iter().copied(),
+            self.history.iter().filter_map(Decision::result),
+        );
+        write!(
+            writer,
+            "{}",
+            svg::heatmap(&items, |a, b| model.probability(a, b))
+        )?;
+
+        if self.include_decisions {
+            writeln!(writer, "<h2>Decisions</h2>")?;
+            writeln!(writer, "<ol>")?;
+            for decision in &self.history {
+                let (left, right) = (
+                    escape(decision.comparison.left),
+                    escape(decision.comparison.right),
+                );
+                match decision.outcome {
+                    Outcome::Left => writeln!(writer, "<li><b>{}</b> beat {}</li>", left, right)?,
+                    Outcome::Right => writeln!(writer, "<li><b>{}</b> beat {}</li>", right, left)?,
+                    Outcome::Draw => writeln!(writer, "<li>{} and {} drew</li>", left, right)?,
+                }
+            }
+            writeln!(writer, "</ol>")?;
+        }
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")?;
+        writer.flush()
+    }
+
+    fn consistency_rows(&self) -> Vec<(&'static str, String)> {
+        let consistency = self.consistency();
+        vec![
+            ("Decisions", consistency.decisions.to_string()),
+            ("Draws", consistency.draws.to_string()),
+            ("Upsets", consistency.upsets.to_string()),
+            (
+                "Agreement with the ranking",
+                consistency
+                    .agreement()
+                    .map(|agreement| format!("{:.0}%", agreement * 100.0))
+                    .unwrap_or_else(|| "n/a".to_owned()),
+            ),
+            ("Circular triads", consistency.circular_triads.to_string()),
+        ]
+    }
+}
+
+/// Escape a value for use in Markdown text and table cells.
+fn markdown(value: impl Display) -> String {
+    let value = value.to_string();
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            '\n' => escaped.push_str("<br>"),
+            '\r' => {}
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Comparisons, Item};
+
+    #[test]
+    fn consistency_counts_upsets_and_cycles() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::new(&comparisons);
+        // Answer such that the preferences form a cycle.
+        while let Some(comparison) = session.current_question() {
+            let left = comparison.left.0;
+            let right = comparison.right.0;
+            session
+                .answer(if (left + 1) % 3 == right {
+                    Outcome::Left
+                } else {
+                    Outcome::Right
+                })
+                .unwrap();
+        }
+
+        let consistency = Report::new(&session).consistency();
+        assert_eq!(consistency.decisions, 3);
+        assert_eq!(consistency.circular_triads, 1);
+        // Every item has a single win, such that all items are tied and nothing is an upset.
+        assert_eq!(consistency.upsets, 0);
+        assert_eq!(consistency.agreement(), Some(1.0));
+    }
+
+    #[test]
+    fn html_escapes_items_and_lists_decisions() {
+        let items = [Item("<b>"), Item("a & b")];
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::new(&comparisons);
+        session.answer(Outcome::Draw).unwrap();
+
+        let mut html = Vec::new();
+        Report::new(&session)
+            .decisions(true)
+            .write_html(&mut html)
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<b><"));
+        assert!(html.contains("<li>Agreement with the ranking: n/a</li>"));
+        assert!(html.contains("drew</li>"));
+        assert!(html.contains("<svg"));
+    }
+
+    #[test]
+    fn markdown_escapes_table_cells() {
+        assert_eq!(markdown("a | *b*\nc"), "a \\| \\*b\\*<br>c");
+    }
+}
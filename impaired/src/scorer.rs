@@ -0,0 +1,223 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{aggregation, Aggregation, ComparisonResult, Decision, Item, Ranking, Scores};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// A model turning the decisions of a [`Session`](crate::Session) into a ranking.
+///
+/// [`Scores`](Scores) implements this trait by counting wins, [`Aggregated`](Aggregated) through
+/// any of the [`Aggregation`](Aggregation) methods. Boxed scorers implement the trait as well,
+/// which allows choosing the scorer at runtime.
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Aggregated, Aggregation, ComparisonResult, Item, Scorer, Scores};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let java = Item("Java");
+///
+/// // E.g. chosen through a command-line option.
+/// let count_wins = false;
+/// let mut scorer: Box<dyn Scorer<&str>> = if count_wins {
+///     Box::new(Scores::new())
+/// } else {
+///     Box::new(Aggregated::new(Aggregation::Copeland))
+/// };
+/// scorer.observe(&ComparisonResult::new(&rust, &cpp).into());
+/// scorer.observe(&ComparisonResult::new(&java, &cpp).into());
+/// scorer.observe(&ComparisonResult::new(&rust, &java).into());
+///
+/// let ranking = scorer.ranking(&[&cpp, &java, &rust]);
+/// assert_eq!(ranking.scores(), vec![(&rust, 2.0), (&java, 1.0), (&cpp, 0.0)]);
+/// ```
+pub trait Scorer<'a, T: Eq + Hash + Ord> {
+    /// Take a decision into account.
+    fn observe(&mut self, decision: &Decision<'a, T>);
+
+    /// Rank the given items according to the decisions observed so far.
+    ///
+    /// Ties should be broken as described for the [`Ranking`](Ranking).
+    fn ranking(&self, items: &[&'a Item<T>]) -> Ranking<'a, T>;
+
+    /// Forget all decisions observed so far, e.g. to observe all but a decision that was taken
+    /// back.
+    fn reset(&mut self);
+}
+
+impl<'a, T: Eq + Hash + Ord, S: Scorer<'a, T> + ?Sized> Scorer<'a, T> for Box<S> {
+    fn observe(&mut self, decision: &Decision<'a, T>) {
+        (**self).observe(decision)
+    }
+
+    fn ranking(&self, items: &[&'a Item<T>]) -> Ranking<'a, T> {
+        (**self).ranking(items)
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+/// Scores count the wins of every item, draws award nothing.
+impl<'a, T: Eq + Hash + Ord> Scorer<'a, T> for Scores<'a, T> {
+    fn observe(&mut self, decision: &Decision<'a, T>) {
+        match decision.result() {
+            Some(result) => self.track(result.winner, result.loser),
+            None => {
+                self.register(decision.comparison.left);
+                self.register(decision.comparison.right);
+                self.head_to_head
+                    .track_draw(decision.comparison.left, decision.comparison.right);
+            }
+        }
+    }
+
+    fn ranking(&self, items: &[&'a Item<T>]) -> Ranking<'a, T> {
+        self.rank(items.iter().copied())
+    }
+
+    fn reset(&mut self) {
+        *self = Scores::new();
+    }
+}
+
+/// A scorer ranking items through one of the [`Aggregation`](Aggregation) methods.
+///
+/// Draws are not taken into account. As every method considers all results at once, the ranking is
+/// recomputed from scratch every time it is requested.
+#[derive(Debug, Clone)]
+pub struct Aggregated<'a, T: Eq + Hash + Ord> {
+    aggregation: Aggregation,
+    results: Vec<ComparisonResult<'a, T>>,
+}
+
+impl<'a, T: Eq + Hash + Ord> Aggregated<'a, T> {
+    /// Create a scorer using the given aggregation method.
+    pub fn new(aggregation: Aggregation) -> Self {
+        Self {
+            aggregation,
+            results: Vec::new(),
+        }
+    }
+
+    /// The aggregation method this scorer uses.
+    pub fn aggregation(&self) -> Aggregation {
+        self.aggregation
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Scorer<'a, T> for Aggregated<'a, T> {
+    fn observe(&mut self, decision: &Decision<'a, T>) {
+        if let Some(result) = decision.result() {
+            self.results.push(result);
+        }
+    }
+
+    fn ranking(&self, items: &[&'a Item<T>]) -> Ranking<'a, T> {
+        let scores: HashMap<&'a Item<T>, f64> = aggregation::aggregate(
+            self.aggregation,
+            items.iter().copied(),
+            self.results.iter().copied(),
+        )
+        .into_iter()
+        .collect();
+
+        // Order the items by their first appearance in the results, then by the order they were
+        // given in, and sort them stably such that this order breaks ties.
+        let mut seen = HashSet::new();
+        let mut order: Vec<&'a Item<T>> = Vec::with_capacity(items.len());
+        for item in self
+            .results
+            .iter()
+            .flat_map(|result| [result.winner, result.loser])
+            .chain(items.iter().copied())
+        {
+            if scores.contains_key(item) && seen.insert(item) {
+                order.push(item);
+            }
+        }
+        order.sort_by(|a, b| aggregation::cmp_scores(self.aggregation, scores[a], scores[b]));
+
+        Ranking::new(order.into_iter().map(|item| (item, scores[item])))
+            .break_ties(self.results.iter().copied())
+    }
+
+    fn reset(&mut self) {
+        self.results.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Outcome;
+
+    #[test]
+    fn scores_rank_every_item_by_wins() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut scores = Scores::new();
+        scores.observe(&ComparisonResult::new(&items[0], &items[2]).into());
+        scores.observe(&Decision {
+            comparison: crate::Comparison::new(&items[0], &items[1]),
+            outcome: Outcome::Draw,
+        });
+
+        assert_eq!(scores[&items[1]], 0);
+        assert_eq!(
+            scores.ranking(&[&items[2], &items[1], &items[0]]).scores(),
+            // Ties are ordered by when the items were first tracked.
+            vec![(&items[0], 1.0), (&items[2], 0.0), (&items[1], 0.0)]
+        );
+    }
+
+    #[test]
+    fn aggregated_ties_are_broken_deterministically() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let mut scorer = Aggregated::new(Aggregation::MeanRank);
+        scorer.observe(&ComparisonResult::new(&items[2], &items[1]).into());
+
+        let order: Vec<_> = scorer
+            .ranking(&[&items[0], &items[1], &items[2]])
+            .iter()
+            .map(|ranked| (ranked.item.0, ranked.rank))
+            .collect();
+        // The lower mean rank is better, the tied items are ordered by their first appearance.
+        assert_eq!(order, vec![(2, 1), (1, 2), (0, 2)]);
+    }
+
+    #[test]
+    fn aggregated_matches_aggregate() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let results = [
+            ComparisonResult::new(&items[0], &items[1]),
+            ComparisonResult::new(&items[1], &items[2]),
+            ComparisonResult::new(&items[2], &items[0]),
+            ComparisonResult::new(&items[3], &items[0]),
+        ];
+        let references: Vec<_> = items.iter().collect();
+
+        for aggregation in [Aggregation::BradleyTerry, Aggregation::Schulze] {
+            let mut scorer = Aggregated::new(aggregation);
+            for result in results {
+                scorer.observe(&result.into());
+            }
+            let ranking: HashMap<_, _> = scorer.ranking(&references).scores().into_iter().collect();
+            let aggregated: HashMap<_, _> =
+                aggregation::aggregate(aggregation, references.iter().copied(), results)
+                    .into_iter()
+                    .collect();
+            assert_eq!(ranking, aggregated);
+        }
+    }
+}
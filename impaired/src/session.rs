@@ -0,0 +1,692 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{
+    Comparison, ComparisonResult, Comparisons, Event, EventLog, HeadToHead, Item, Ranking,
+    RetainWinner, Scorer, Scores, SessionState, StoppingCriterion, Strategy, WarmStart,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    hash::Hash,
+};
+
+/// The answer to a comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The [`left`](Comparison::left) item of the comparison won.
+    Left,
+    /// The [`right`](Comparison::right) item of the comparison won.
+    Right,
+    /// Neither item is preferred over the other.
+    Draw,
+}
+
+/// A comparison along with the outcome it was answered with.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Decision<'a, T: Eq + Hash + Ord> {
+    /// The comparison that was answered.
+    pub comparison: Comparison<'a, T>,
+    /// The outcome the comparison was answered with.
+    pub outcome: Outcome,
+}
+
+impl<'a, T: Eq + Hash + Ord> Decision<'a, T> {
+    /// The winner of the comparison, unless it was a draw.
+    pub fn winner(&self) -> Option<&'a Item<T>> {
+        match self.outcome {
+            Outcome::Left => Some(self.comparison.left),
+            Outcome::Right => Some(self.comparison.right),
+            Outcome::Draw => None,
+        }
+    }
+
+    /// The result of the comparison, unless it was a draw.
+    pub fn result(&self) -> Option<ComparisonResult<'a, T>> {
+        self.winner()
+            .map(|winner| ComparisonResult::new(winner, self.comparison.other(winner)))
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> From<ComparisonResult<'a, T>> for Decision<'a, T> {
+    fn from(result: ComparisonResult<'a, T>) -> Self {
+        let outcome = if result.winner == result.comparison.left {
+            Outcome::Left
+        } else {
+            Outcome::Right
+        };
+        Self {
+            comparison: result.comparison,
+            outcome,
+        }
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Clone for Decision<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> Copy for Decision<'a, T> {}
+
+/// A ranking session, guiding through the comparisons one question at a time.
+///
+/// Unlike iterating over the [`RetainItemIterator`](crate::RetainItemIterator) directly, where
+/// tracking the winner through a [`ComparisonResultTracker`](crate::ComparisonResultTracker) is
+/// optional and silently changes which comparisons follow, the session makes every step explicit:
+/// the [current question](Session::current_question) stays the same until it is
+/// [answered](Session::answer), and every answer is recorded.
+///
+/// Which question is asked next is decided by a [`Strategy`](Strategy). Sessions created through
+/// [`Session::new`](Session::new) use the [`RetainWinner`](RetainWinner) strategy, other strategies
+/// can be provided through [`Session::with_strategy`](Session::with_strategy). Sessions can also
+/// start from the results a [`Prior`](crate::Prior) established, through
+/// [`Session::warm_start`](Session::warm_start). The
+/// [ranking](Session::ranking) is determined by a [`Scorer`](Scorer), by default the
+/// [`Scores`](Scores) counting wins; another one can be chosen through
+/// [`Session::scorer`](Session::scorer).
+///
+/// ## Example
+///
+/// ```rust
+/// # use impaired::{Comparisons, Item, Outcome, Session};
+/// let rust = Item("Rust");
+/// let cpp = Item("C++");
+/// let java = Item("Java");
+/// let comparisons = Comparisons::new([&rust, &cpp, &java]);
+///
+/// let mut session = Session::new(&comparisons);
+/// while let Some(comparison) = session.current_question() {
+///     let outcome = if comparison.left == &rust {
+///         Outcome::Left
+///     } else if comparison.right == &rust {
+///         Outcome::Right
+///     } else {
+///         Outcome::Draw
+///     };
+///     session.answer(outcome)?;
+/// }
+///
+/// assert!(session.is_finished());
+/// assert_eq!(session.history().len(), 3);
+/// let ranking = session.ranking();
+/// assert_eq!((ranking[0].item, ranking[0].score), (&rust, 2.0));
+/// # Ok::<(), impaired::SessionError>(())
+/// ```
+pub struct Session<'a, T: Eq + Hash + Ord, S = RetainWinner, R = Scores<'a, T>> {
+    items: Vec<&'a Item<T>>,
+    remaining: Comparisons<'a, T>,
+    strategy: S,
+    scorer: R,
+    stopping_criterion: StoppingCriterion,
+    current: Option<Comparison<'a, T>>,
+    history: Vec<Decision<'a, T>>,
+    /// The number of decisions at the start of the history that were established before the
+    /// session started, which cannot be undone.
+    established: usize,
+    /// The skipped questions, along with the number of decisions made before they were skipped.
+    skipped: Vec<(usize, Comparison<'a, T>)>,
+    /// The wins of every item, which the stopping criterion and the strategy are based on whichever
+    /// scorer ranks the items. Counting them is cheap, so they are kept even if the scorer counts
+    /// wins as well.
+    scores: Scores<'a, T>,
+}
+
+impl<'a, T: Eq + Hash + Ord> Session<'a, T> {
+    /// Start a session over the given comparisons, retaining the winner of every question for the
+    /// next one.
+    pub fn new(comparisons: &Comparisons<'a, T>) -> Self {
+        Self::with_strategy(comparisons, RetainWinner)
+    }
+
+    /// Start a session over the comparisons a prior left to ask, retaining the winner of every
+    /// question for the next one.
+    ///
+    /// See [`Session::warm_start_with_strategy`](Session::warm_start_with_strategy).
+    pub fn warm_start(warm_start: &WarmStart<'a, T>) -> Self {
+        Self::warm_start_with_strategy(warm_start, RetainWinner)
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord, S: Strategy<'a, T>> Session<'a, T, S> {
+    /// Start a session over the given comparisons, choosing the questions through the given
+    /// strategy.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the strategy returns a comparison that is not part of the given comparisons.
+    pub fn with_strategy(comparisons: &Comparisons<'a, T>, strategy: S) -> Self {
+        Self::start(comparisons.items(), comparisons, &[], strategy)
+    }
+
+    /// Start a session over the comparisons a prior left to ask, choosing the questions through the
+    /// given strategy.
+    ///
+    /// The [established results](WarmStart::established) count as if they had been answered
+    /// before the first question: they are part of the [history](Session::history), the
+    /// [scores](Session::scores) and the [ranking](Session::ranking), but cannot be
+    /// [undone](Session::undo). Every item of the comparisons the prior was applied to is ranked,
+    /// including those without any comparison left to ask.
+    ///
+    /// ```rust
+    /// # use impaired::{Comparisons, Item, Prior, Session};
+    /// let rust = Item("Rust");
+    /// let cpp = Item("C++");
+    /// let java = Item("Java");
+    /// let zig = Item("Zig");
+    ///
+    /// // Last time, Rust was ranked above C++, which was ranked above Java. Zig is new.
+    /// let comparisons = Comparisons::new([&rust, &cpp, &java, &zig]);
+    /// let warm_start = comparisons.warm_start(&Prior::Ordering(vec!["Rust", "C++", "Java"]));
+    ///
+    /// let session = Session::warm_start(&warm_start);
+    /// assert_eq!(session.history().len(), 3);
+    /// assert_eq!(session.remaining(), 3);
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the strategy returns a comparison that is not part of the remaining comparisons.
+    pub fn warm_start_with_strategy(warm_start: &WarmStart<'a, T>, strategy: S) -> Self {
+        Self::start(
+            warm_start.comparisons.items.clone(),
+            &warm_start.comparisons,
+            &warm_start.established,
+            strategy,
+        )
+    }
+
+    fn start(
+        items: Vec<&'a Item<T>>,
+        comparisons: &Comparisons<'a, T>,
+        established: &[ComparisonResult<'a, T>],
+        strategy: S,
+    ) -> Self {
+        let mut session = Self {
+            items,
+            remaining: comparisons.clone(),
+            strategy,
+            scorer: Scores::new(),
+            stopping_criterion: StoppingCriterion::default(),
+            current: None,
+            history: established.iter().map(|&result| result.into()).collect(),
+            established: established.len(),
+            skipped: Vec::new(),
+            scores: Scores::new(),
+        };
+        for decision in &session.history {
+            session.scores.observe(decision);
+            session.scorer.observe(decision);
+        }
+        session.current = session.next_question();
+        session
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord, S: Strategy<'a, T>, R: Scorer<'a, T>> Session<'a, T, S, R> {
+    /// Rank the items through the given scorer instead, taking all decisions made so far into
+    /// account.
+    pub fn scorer<R2: Scorer<'a, T>>(self, mut scorer: R2) -> Session<'a, T, S, R2> {
+        for decision in &self.history {
+            scorer.observe(decision);
+        }
+        Session {
+            items: self.items,
+            remaining: self.remaining,
+            strategy: self.strategy,
+            scorer,
+            stopping_criterion: self.stopping_criterion,
+            current: self.current,
+            history: self.history,
+            established: self.established,
+            skipped: self.skipped,
+            scores: self.scores,
+        }
+    }
+
+    /// Replace the scorer of a running session, e.g. a boxed scorer chosen at runtime, taking all
+    /// decisions made so far into account.
+    pub fn set_scorer(&mut self, mut scorer: R) {
+        for decision in &self.history {
+            scorer.observe(decision);
+        }
+        self.scorer = scorer;
+    }
+
+    /// Set the criterion that decides whether the session can finish before all comparisons have
+    /// been answered.
+    ///
+    /// See [`StoppingCriterion`](StoppingCriterion) for the available criteria.
+    pub fn stopping_criterion(mut self, stopping_criterion: StoppingCriterion) -> Self {
+        self.stopping_criterion = stopping_criterion;
+        if self.is_settled() {
+            self.current = None;
+        }
+        self
+    }
+
+    /// The comparison that has to be answered next, or `None` if the session is finished.
+    pub fn current_question(&self) -> Option<Comparison<'a, T>> {
+        self.current
+    }
+
+    /// Answer the current question, advancing the session to the next one.
+    ///
+    /// Returns the recorded decision, or an error if the session is already finished.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the strategy returns a comparison that has already been answered.
+    pub fn answer(&mut self, outcome: Outcome) -> Result<Decision<'a, T>, SessionError> {
+        let comparison = self.current.ok_or(SessionError::Finished)?;
+        let decision = Decision {
+            comparison,
+            outcome,
+        };
+
+        self.scores.observe(&decision);
+        self.scorer.observe(&decision);
+        self.remaining.remove(&comparison);
+        self.history.push(decision);
+        self.current = self.next_question();
+
+        Ok(decision)
+    }
+
+    /// Skip the current question without answering it, and move on to the next one.
+    ///
+    /// The skipped comparison is not asked again and not taken into account for the ranking, as if
+    /// it had never been part of the session. It is returned on success, or
+    /// [`SessionError::Finished`](SessionError::Finished) if there is no question left to skip.
+    pub fn skip(&mut self) -> Result<Comparison<'a, T>, SessionError> {
+        let comparison = self.current.ok_or(SessionError::Finished)?;
+        self.remaining.remove(&comparison);
+        self.skipped.push((self.history.len(), comparison));
+        self.current = self.next_question();
+        Ok(comparison)
+    }
+
+    /// Take back the last answer or skip, such that its question is the current one again.
+    ///
+    /// The scores and the scorer forget the decision taken back, as if it had never been made.
+    /// Returns the question that is asked again, or `None` if nothing has been answered or skipped
+    /// yet. Results established before the session started cannot be taken back.
+    pub fn undo(&mut self) -> Option<Comparison<'a, T>> {
+        let comparison = match self.skipped.last() {
+            // The last skip happened after the last decision.
+            Some(&(decisions, _)) if decisions == self.history.len() => self.skipped.pop()?.1,
+            _ if self.history.len() == self.established => return None,
+            _ => {
+                let decision = self.history.pop()?;
+                self.scores = Scores::new();
+                self.scorer.reset();
+                for decision in &self.history {
+                    self.scores.observe(decision);
+                    self.scorer.observe(decision);
+                }
+                decision.comparison
+            }
+        };
+        self.remaining.insert(&comparison);
+        self.current = Some(comparison);
+        Some(comparison)
+    }
+
+    /// All questions that were [skipped](Session::skip), in the order they were skipped.
+    pub fn skipped(&self) -> Vec<Comparison<'a, T>> {
+        self.skipped
+            .iter()
+            .map(|(_, comparison)| *comparison)
+            .collect()
+    }
+
+    /// The number of comparisons that have neither been answered nor skipped yet.
+    ///
+    /// This is the number of questions left at most, the stopping criterion might finish the
+    /// session earlier.
+    pub fn remaining(&self) -> usize {
+        if self.is_finished() {
+            0
+        } else {
+            self.remaining.len()
+        }
+    }
+
+    /// Whether every question has been answered, or the stopping criterion is met.
+    pub fn is_finished(&self) -> bool {
+        self.current.is_none()
+    }
+
+    /// All decisions made so far, in the order they were made.
+    pub fn history(&self) -> &[Decision<'a, T>] {
+        &self.history
+    }
+
+    /// The results of all decisions made so far that were not draws, in the order they were made.
+    pub fn results(&self) -> Vec<ComparisonResult<'a, T>> {
+        self.history
+            .iter()
+            .filter_map(|decision| decision.result())
+            .collect()
+    }
+
+    /// The number of wins of every item according to the decisions made so far.
+    ///
+    /// Draws are part of the [history](Session::history), but do not award a win to either item.
+    /// The stopping criterion and the strategy rely on these scores, independent of the scorer
+    /// ranking the items.
+    pub fn scores(&self) -> &Scores<'a, T> {
+        &self.scores
+    }
+
+    /// The ranking of all items according to the decisions made so far and the scorer, ordered from
+    /// best to worst.
+    pub fn ranking(&self) -> Ranking<'a, T> {
+        self.scorer.ranking(&self.items)
+    }
+
+    /// How every item fared against every other item according to the decisions made so far, with
+    /// the items listed in the order of the [ranking](Session::ranking).
+    pub fn head_to_head(&self) -> HeadToHead<'a, T> {
+        self.head_to_head_ordered_by(&self.ranking())
+    }
+
+    /// How every item fared against every other item, listed in the order of the given ranking.
+    pub(crate) fn head_to_head_ordered_by(&self, ranking: &Ranking<'a, T>) -> HeadToHead<'a, T> {
+        self.scores
+            .head_to_head()
+            .ordered(ranking.iter().map(|ranked| ranked.item))
+    }
+
+    fn is_settled(&self) -> bool {
+        self.stopping_criterion
+            .is_met(&self.remaining.items, &self.scores, |index| {
+                self.remaining.pairs.count(index)
+            })
+    }
+
+    fn next_question(&mut self) -> Option<Comparison<'a, T>> {
+        if self.remaining.is_empty() || self.is_settled() {
+            return None;
+        }
+        let state = SessionState {
+            items: &self.items,
+            remaining: &self.remaining,
+            history: &self.history,
+            scores: &self.scores,
+        };
+        let comparison = self.strategy.next_comparison(&state)?;
+        assert!(
+            self.remaining.contains(&comparison),
+            "the strategy has to choose one of the remaining comparisons"
+        );
+        Some(comparison)
+    }
+}
+
+impl<'a, T: Clone + Eq + Hash + Ord, S, R> Session<'a, T, S, R> {
+    /// An [`EventLog`](EventLog) of the session, listing all items followed by all decisions and
+    /// skipped questions in the order they were made.
+    ///
+    /// Answering the questions of a new session over the same items according to the log restores
+    /// the session, as long as its strategy is deterministic.
+    pub fn event_log(&self) -> EventLog<T> {
+        let mut event_log = EventLog::new();
+        for item in &self.items {
+            event_log.push(Event::Item(item.0.clone()));
+        }
+        let mut skipped = self.skipped.iter().peekable();
+        for (index, decision) in self.history.iter().enumerate() {
+            while let Some((_, comparison)) = skipped.next_if(|(before, _)| *before == index) {
+                event_log.push(Event::Skip {
+                    left: comparison.left.0.clone(),
+                    right: comparison.right.0.clone(),
+                });
+            }
+            let (left, right) = (decision.comparison.left, decision.comparison.right);
+            event_log.push(match decision.outcome {
+                Outcome::Left => Event::Result {
+                    winner: left.0.clone(),
+                    loser: right.0.clone(),
+                },
+                Outcome::Right => Event::Result {
+                    winner: right.0.clone(),
+                    loser: left.0.clone(),
+                },
+                Outcome::Draw => Event::Draw {
+                    left: left.0.clone(),
+                    right: right.0.clone(),
+                },
+            });
+        }
+        for (_, comparison) in skipped {
+            event_log.push(Event::Skip {
+                left: comparison.left.0.clone(),
+                right: comparison.right.0.clone(),
+            });
+        }
+        event_log
+    }
+}
+
+/// An error that occurred while interacting with a [`Session`](Session).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    /// The session is finished, there is no question left to answer.
+    Finished,
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Finished => write!(f, "the session is finished"),
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Aggregated, Aggregation, Prior};
+    use std::cmp;
+
+    #[test]
+    fn question_stays_until_answered() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::new(&comparisons);
+
+        let first = session.current_question().unwrap();
+        assert_eq!(session.current_question(), Some(first));
+
+        let decision = session.answer(Outcome::Right).unwrap();
+        assert_eq!(decision.winner(), Some(first.right));
+        // The winner is retained for the next question.
+        let second = session.current_question().unwrap();
+        assert!(second.left == first.right || second.right == first.right);
+    }
+
+    #[test]
+    fn answering_a_finished_session_fails() {
+        let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::new(&comparisons);
+
+        while !session.is_finished() {
+            session.answer(Outcome::Draw).unwrap();
+        }
+        assert_eq!(session.answer(Outcome::Left), Err(SessionError::Finished));
+        assert_eq!(session.history().len(), 3);
+        assert!(session.ranking().iter().all(|ranked| ranked.rank == 1));
+        assert_eq!(session.head_to_head().total(&items[0]).draws, 2);
+    }
+
+    #[test]
+    fn stopping_criterion_finishes_early() {
+        let items: Vec<Item<usize>> = (0..8).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session =
+            Session::new(&comparisons).stopping_criterion(StoppingCriterion::TopSettled(1));
+
+        while let Some(comparison) = session.current_question() {
+            let outcome = if comparison.left > comparison.right {
+                Outcome::Left
+            } else {
+                Outcome::Right
+            };
+            session.answer(outcome).unwrap();
+        }
+        assert!(session.history().len() < comparisons.len());
+        assert_eq!(session.ranking().scores()[0], (&items[7], 7.0));
+    }
+
+    #[test]
+    fn scorers_can_be_switched_at_runtime() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session =
+            Session::new(&comparisons).scorer(Box::new(Scores::new()) as Box<dyn Scorer<usize>>);
+
+        // A cycle between the first three items, which all beat the last one.
+        while let Some(comparison) = session.current_question() {
+            let winner = match (comparison.left.0, comparison.right.0) {
+                (0, 1) | (1, 2) => comparison.left,
+                (0, 2) => comparison.right,
+                _ => cmp::min(comparison.left, comparison.right),
+            };
+            session
+                .answer(if winner == comparison.left {
+                    Outcome::Left
+                } else {
+                    Outcome::Right
+                })
+                .unwrap();
+        }
+        assert_eq!(session.ranking().scores()[3], (&items[3], 0.0));
+
+        session.set_scorer(Box::new(Aggregated::new(Aggregation::BradleyTerry)));
+        let ranking = session.ranking();
+        assert_eq!(ranking[3].item, &items[3]);
+        assert!(ranking[3].score < 1.0);
+    }
+
+    #[test]
+    fn undo_takes_back_the_last_answer_or_skip() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session =
+            Session::new(&comparisons).scorer(Aggregated::new(Aggregation::SumOfWins));
+        assert_eq!(session.undo(), None);
+
+        let first = session.current_question().unwrap();
+        session.answer(Outcome::Left).unwrap();
+        let second = session.current_question().unwrap();
+        session.skip().unwrap();
+        let third = session.current_question().unwrap();
+        session.answer(Outcome::Right).unwrap();
+        let wins = |session: &Session<usize, RetainWinner, Aggregated<usize>>| {
+            let ranking = session.ranking();
+            ranking.iter().map(|ranked| ranked.score).sum::<f64>()
+        };
+        assert_eq!(wins(&session), 2.0);
+
+        assert_eq!(session.undo(), Some(third));
+        assert_eq!(session.history().len(), 1);
+        assert_eq!(wins(&session), 1.0);
+        assert_eq!(session.undo(), Some(second));
+        assert!(session.skipped().is_empty());
+        assert_eq!(session.remaining(), comparisons.len() - 1);
+        assert_eq!(session.undo(), Some(first));
+        assert_eq!(session.current_question(), Some(first));
+        assert!(session.history().is_empty());
+        assert_eq!(session.remaining(), comparisons.len());
+        assert_eq!(wins(&session), 0.0);
+        assert_eq!(session.scores().head_to_head().total(first.left).wins, 0);
+        assert_eq!(session.undo(), None);
+    }
+
+    #[test]
+    fn warm_starts_count_the_established_results() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        // Every comparison is established, so no question is left.
+        let mut session =
+            Session::warm_start(&comparisons.warm_start(&Prior::Ordering(vec![3, 2, 1, 0])));
+        assert!(session.is_finished());
+        assert_eq!(session.history().len(), 6);
+        assert_eq!(
+            session.ranking().scores(),
+            vec![
+                (&items[3], 3.0),
+                (&items[2], 2.0),
+                (&items[1], 1.0),
+                (&items[0], 0.0)
+            ]
+        );
+        assert_eq!(session.undo(), None);
+
+        // Item 0 is new, so only the comparisons against it are left.
+        let warm_start = comparisons.warm_start(&Prior::Ordering(vec![3, 2, 1]));
+        let mut session =
+            Session::warm_start(&warm_start).scorer(Aggregated::new(Aggregation::SumOfWins));
+        assert_eq!(session.ranking().len(), 4);
+        assert_eq!(session.scores().head_to_head().total(&items[3]).wins, 2);
+        let question = session.current_question().unwrap();
+        assert!(question.left == &items[0] || question.right == &items[0]);
+        session.answer(Outcome::Left).unwrap();
+        assert_eq!(session.undo(), Some(question));
+        assert_eq!(session.undo(), None);
+        assert_eq!(session.history().len(), 3);
+        assert_eq!(session.remaining(), 3);
+    }
+
+    #[test]
+    fn event_log_lists_items_decisions_and_skips() {
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::new(&comparisons);
+        let first = session.current_question().unwrap();
+        session.answer(Outcome::Right).unwrap();
+        let skipped = session.skip().unwrap();
+        let third = session.current_question().unwrap();
+        session.answer(Outcome::Draw).unwrap();
+
+        assert_ne!(third, skipped);
+        assert_eq!(session.skipped(), vec![skipped]);
+        assert_eq!(session.remaining(), comparisons.len() - 3);
+        assert_eq!(session.history().len(), 2);
+
+        let event_log = session.event_log();
+        assert_eq!(
+            event_log.items().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            &event_log.events()[4..],
+            &[
+                Event::Result {
+                    winner: first.right.0,
+                    loser: first.left.0,
+                },
+                Event::Skip {
+                    left: skipped.left.0,
+                    right: skipped.right.0,
+                },
+                Event::Draw {
+                    left: third.left.0,
+                    right: third.right.0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+use crate::{Comparison, Comparisons, Decision, Item, Scores};
+use std::{cmp, hash::Hash};
+
+/// A view on the state of a [`Session`](crate::Session), from which a [`Strategy`](Strategy)
+/// chooses the next question.
+pub struct SessionState<'s, 'a, T: Eq + Hash + Ord> {
+    pub(crate) items: &'s [&'a Item<T>],
+    pub(crate) remaining: &'s Comparisons<'a, T>,
+    pub(crate) history: &'s [Decision<'a, T>],
+    pub(crate) scores: &'s Scores<'a, T>,
+}
+
+impl<'s, 'a, T: Eq + Hash + Ord> SessionState<'s, 'a, T> {
+    /// All items of the session, in ascending order.
+    pub fn items(&self) -> &'s [&'a Item<T>] {
+        self.items
+    }
+
+    /// The comparisons that have not been answered yet.
+    pub fn remaining(&self) -> &'s Comparisons<'a, T> {
+        self.remaining
+    }
+
+    /// All decisions made so far, in the order they were made.
+    pub fn history(&self) -> &'s [Decision<'a, T>] {
+        self.history
+    }
+
+    /// The number of wins of every item according to the decisions made so far.
+    pub fn scores(&self) -> &'s Scores<'a, T> {
+        self.scores
+    }
+}
+
+/// A strategy choosing which question a [`Session`](crate::Session) asks next.
+///
+/// The strategy is consulted once the session starts and after every answer, and returns one of the
+/// [remaining](SessionState::remaining) comparisons, or `None` to finish the session early.
+///
+/// ## Panics
+///
+/// The session panics if the strategy returns a comparison that is not part of the remaining
+/// comparisons.
+///
+/// ## Example
+///
+/// A strategy always asking for the lowest remaining pair:
+///
+/// ```rust
+/// # use impaired::{Comparison, Comparisons, Item, Outcome, Session, SessionState, Strategy};
+/// struct InOrder;
+///
+/// impl<'a, T: Eq + std::hash::Hash + Ord> Strategy<'a, T> for InOrder {
+///     fn next_comparison(&mut self, state: &SessionState<'_, 'a, T>) -> Option<Comparison<'a, T>> {
+///         state.remaining().iter().min_by_key(|comparison| (comparison.left, comparison.right))
+///     }
+/// }
+///
+/// let items: Vec<Item<usize>> = (0..3).map(Item).collect();
+/// let comparisons = Comparisons::new(items.iter());
+/// let mut session = Session::with_strategy(&comparisons, InOrder);
+/// assert_eq!(
+///     session.current_question(),
+///     Some(Comparison::new(&items[0], &items[1]))
+/// );
+/// session.answer(Outcome::Right)?;
+/// assert_eq!(
+///     session.current_question(),
+///     Some(Comparison::new(&items[0], &items[2]))
+/// );
+/// # Ok::<(), impaired::SessionError>(())
+/// ```
+pub trait Strategy<'a, T: Eq + Hash + Ord> {
+    /// Choose the next question to ask, or `None` to finish the session.
+    fn next_comparison(&mut self, state: &SessionState<'_, 'a, T>) -> Option<Comparison<'a, T>>;
+}
+
+/// The strategy retaining the winner of the previous question for the next one.
+///
+/// This is the same order the [`RetainItemIterator`](crate::RetainItemIterator) yields comparisons
+/// in: the winner of the previous question (or, after a draw, either of its items) is compared
+/// against the partner with the most remaining comparisons. If neither item of the previous
+/// question has a comparison left, the session continues with any remaining comparison.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RetainWinner;
+
+impl<'a, T: Eq + Hash + Ord> Strategy<'a, T> for RetainWinner {
+    fn next_comparison(&mut self, state: &SessionState<'_, 'a, T>) -> Option<Comparison<'a, T>> {
+        let remaining = state.remaining;
+        let (winner, loser) = match state.history.last() {
+            Some(decision) => match decision.result() {
+                Some(result) => (result.winner, result.loser),
+                None => (decision.comparison.left, decision.comparison.right),
+            },
+            None => {
+                let seed_comparison = remaining.iter().next()?;
+                (seed_comparison.left, seed_comparison.right)
+            }
+        };
+
+        let (index, partner) = retain_pair(remaining, winner, loser)?;
+        Some(Comparison::new(
+            remaining.items[index],
+            remaining.items[partner],
+        ))
+    }
+}
+
+/// Choose the remaining pair retaining `winner`, or `loser` if the winner has no partner left,
+/// preferring the partner with the most remaining comparisons.
+///
+/// Falls back to any remaining pair if neither item has a partner left.
+pub(crate) fn retain_pair<'a, T: Eq + Hash + Ord>(
+    remaining: &Comparisons<'a, T>,
+    winner: &'a Item<T>,
+    loser: &'a Item<T>,
+) -> Option<(usize, usize)> {
+    // Prefer the partner with the most remaining comparisons, which spreads the comparisons
+    // across the items and lets the retained item meet its strongest contenders early.
+    let partner = |item: &'a Item<T>| {
+        let index = remaining
+            .index(item)
+            .expect("the referenced item has to exist");
+        remaining
+            .pairs
+            .partners(index)
+            .min_by_key(|partner| cmp::Reverse(remaining.pairs.count(*partner)))
+            .map(|partner| (index, partner))
+    };
+    partner(winner)
+        .or_else(|| partner(loser))
+        .or_else(|| remaining.pairs.iter().next())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Outcome, Session};
+
+    #[test]
+    fn retain_winner_matches_the_retain_item_iterator() {
+        let items: Vec<Item<usize>> = (0..7).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+
+        let mut session = Session::with_strategy(&comparisons, RetainWinner);
+        for (comparison, result_tracker) in comparisons.retain_item_iterator() {
+            assert_eq!(session.current_question(), Some(comparison));
+            let winner = cmp::max(comparison.left, comparison.right);
+            result_tracker.winner(winner);
+            session
+                .answer(if winner == comparison.left {
+                    Outcome::Left
+                } else {
+                    Outcome::Right
+                })
+                .unwrap();
+        }
+        assert!(session.is_finished());
+    }
+
+    #[test]
+    fn strategies_can_finish_early() {
+        /// Ask about every pair not containing the given item, then finish.
+        struct Without(usize);
+
+        impl<'a> Strategy<'a, usize> for Without {
+            fn next_comparison(
+                &mut self,
+                state: &SessionState<'_, 'a, usize>,
+            ) -> Option<Comparison<'a, usize>> {
+                assert_eq!(state.items().len(), 4);
+                state
+                    .remaining()
+                    .iter()
+                    .find(|comparison| comparison.left.0 != self.0 && comparison.right.0 != self.0)
+            }
+        }
+
+        let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+        let comparisons = Comparisons::new(items.iter());
+        let mut session = Session::with_strategy(&comparisons, Without(3));
+        while !session.is_finished() {
+            session.answer(Outcome::Draw).unwrap();
+        }
+        assert_eq!(session.history().len(), 3);
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright Pit Kleyersburg <pitkley@googlemail.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified or distributed
+// except according to those terms.
+
+//! Render rankings as self-contained SVG charts.
+//!
+//! The charts are plain SVG documents without scripts or external resources, such that they can be
+//! embedded into reports or web pages as they are:
+//!
+//! - [`heatmap`](heatmap) shows the probability of every item winning against every other item,
+//!   e.g. according to a [`BradleyTerry`](crate::BradleyTerry) model.
+//! - [`score_chart`](score_chart) shows the score of every item as a bar, along with its
+//!   confidence interval, e.g. as determined by a [`Bootstrap`](crate::Bootstrap).
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use impaired::{svg, Aggregation, Bootstrap, BradleyTerry, Comparisons, Item, Outcome, Session};
+//! let items: Vec<Item<usize>> = (0..4).map(Item).collect();
+//! let comparisons = Comparisons::new(items.iter());
+//! let mut session = Session::new(&comparisons);
+//! while let Some(comparison) = session.current_question() {
+//!     // The bigger number always wins.
+//!     session.answer(if comparison.left > comparison.right {
+//!         Outcome::Left
+//!     } else {
+//!         Outcome::Right
+//!     })?;
+//! }
+//!
+//! let ranked: Vec<_> = session.ranking().iter().map(|ranked| ranked.item).collect();
+//! let model = BradleyTerry::fit(ranked.iter().copied(), session.results());
+//! let heatmap = svg::heatmap(&ranked, |a, b| model.probability(a, b));
+//! assert!(heatmap.starts_with("<svg"));
+//!
+//! let intervals = Bootstrap::new(ranked.iter().copied(), session.results())
+//!     .resamples(100)
+//!     .run(Aggregation::SumOfWins);
+//! let chart = svg::score_chart(&intervals, Aggregation::SumOfWins);
+//! assert!(chart.contains("<title>3: 3"));
+//! # Ok::<(), impaired::SessionError>(())
+//! ```
+
+use crate::{Aggregation, Item, RankInterval};
+use std::fmt::{Display, Write};
+
+const CELL_SIZE: f64 = 28.0;
+const BAR_HEIGHT: f64 = 20.0;
+const BAR_SPACING: f64 = 8.0;
+const CHART_WIDTH: f64 = 400.0;
+const MARGIN: f64 = 10.0;
+/// A rough estimate of the width of a character, to make room for labels.
+const CHAR_WIDTH: f64 = 7.0;
+const FONT: &str = "font-family=\"sans-serif\" font-size=\"12\"";
+
+/// Render the probability of every item winning against every other item as a heatmap.
+///
+/// Rows and columns are ordered like the given items, which should usually be the final ranking.
+/// The cell in the row of item `a` and the column of item `b` shows the probability of `a` winning
+/// against `b`, as given by `probability(a, b)`, from red (`0`) over white (`0.5`) to blue (`1`).
+pub fn heatmap<T: Display>(
+    items: &[&Item<T>],
+    probability: impl Fn(&Item<T>, &Item<T>) -> f64,
+) -> String {
+    let labels: Vec<String> = items.iter().map(escape).collect();
+    let label_width = label_width(items);
+    let grid = CELL_SIZE * items.len() as f64;
+    let left = MARGIN + label_width;
+    // The column labels are rotated, such that they need as much room as the row labels.
+    let top = MARGIN + label_width;
+    let width = left + grid + MARGIN;
+    let height = top + grid + MARGIN;
+
+    let mut svg = open(width, height);
+    for (column, label) in labels.iter().enumerate() {
+        let x = left + CELL_SIZE * (column as f64 + 0.5);
+        let y = top - 4.0;
+        let _ = writeln!(
+            svg,
+            "<text x=\"{x:.1}\" y=\"{y:.1}\" {FONT} transform=\"rotate(-60 {x:.1} {y:.1})\">{label}</text>",
+            x = x,
+            y = y,
+            FONT = FONT,
+            label = label,
+        );
+    }
+    for (row, (a, a_label)) in items.iter().zip(&labels).enumerate() {
+        let y = top + CELL_SIZE * row as f64;
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" {} text-anchor=\"end\">{}</text>",
+            left - 4.0,
+            y + CELL_SIZE / 2.0 + 4.0,
+            FONT,
+            a_label
+        );
+        for (column, (b, b_label)) in items.iter().zip(&labels).enumerate() {
+            let x = left + CELL_SIZE * column as f64;
+            if row == column {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#dddddd\"/>",
+                    x, y, CELL_SIZE, CELL_SIZE
+                );
+                continue;
+            }
+            let probability = probability(a, b).clamp(0.0, 1.0);
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">\
+                 <title>{} beats {}: {:.0}%</title></rect>",
+                x,
+                y,
+                CELL_SIZE,
+                CELL_SIZE,
+                color(probability),
+                a_label,
+                b_label,
+                probability * 100.0
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render the scores of the given items as a horizontal bar chart, with their confidence intervals
+/// as whiskers.
+///
+/// The bars are ordered like the given intervals, which [`Bootstrap::run`](crate::Bootstrap::run)
+/// returns in the order of the ranking, and the aggregation should be the one the intervals were
+/// determined through. The axis starts at zero, unless lower scores are better (see
+/// [`Aggregation::lower_is_better`](Aggregation::lower_is_better)): then it is reversed and starts
+/// at the largest score, such that better items still get longer bars.
+pub fn score_chart<T: Display>(intervals: &[RankInterval<T>], aggregation: Aggregation) -> String {
+    let items: Vec<&Item<T>> = intervals.iter().map(|interval| interval.item).collect();
+    let label_width = label_width(&items);
+    let maximum = intervals
+        .iter()
+        .map(|interval| interval.upper_score.max(interval.score))
+        .fold(0.0, f64::max);
+    let lower_is_better = aggregation.lower_is_better();
+    let scale = |score: f64| {
+        if maximum > 0.0 {
+            let score = score.clamp(0.0, maximum);
+            let length = if lower_is_better {
+                maximum - score
+            } else {
+                score
+            };
+            length / maximum * CHART_WIDTH
+        } else {
+            0.0
+        }
+    };
+    let left = MARGIN + label_width;
+    let width = left + CHART_WIDTH + MARGIN + 60.0;
+    let height = MARGIN * 2.0 + (BAR_HEIGHT + BAR_SPACING) * intervals.len() as f64;
+
+    let mut svg = open(width, height);
+    for (index, interval) in intervals.iter().enumerate() {
+        let label = escape(interval.item);
+        let y = MARGIN + (BAR_HEIGHT + BAR_SPACING) * index as f64;
+        let center = y + BAR_HEIGHT / 2.0;
+        let lower = left + scale(interval.lower_score);
+        let upper = left + scale(interval.upper_score);
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" {} text-anchor=\"end\">{}</text>",
+            left - 4.0,
+            center + 4.0,
+            FONT,
+            label
+        );
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4393c3\">\
+             <title>{}: {} ({} to {})</title></rect>",
+            left,
+            y,
+            scale(interval.score),
+            BAR_HEIGHT,
+            label,
+            format_score(interval.score),
+            format_score(interval.lower_score),
+            format_score(interval.upper_score)
+        );
+        let _ = writeln!(
+            svg,
+            "<path d=\"M{lower:.1} {center:.1}H{upper:.1}M{lower:.1} {top:.1}V{bottom:.1}M{upper:.1} {top:.1}V{bottom:.1}\" \
+             stroke=\"#222222\" fill=\"none\"/>",
+            lower = lower,
+            upper = upper,
+            center = center,
+            top = center - BAR_HEIGHT / 4.0,
+            bottom = center + BAR_HEIGHT / 4.0,
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" {}>{}</text>",
+            left + scale(interval.score).max(lower - left).max(upper - left) + 6.0,
+            center + 4.0,
+            FONT,
+            format_score(interval.score)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn open(width: f64, height: f64) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n",
+        w = width.ceil(),
+        h = height.ceil(),
+    )
+}
+
+fn label_width<T: Display>(items: &[&Item<T>]) -> f64 {
+    let chars = items
+        .iter()
+        .map(|item| item.to_string().chars().count())
+        .max()
+        .unwrap_or_default();
+    chars as f64 * CHAR_WIDTH
+}
+
+/// Interpolate from red (`0`) over white (`0.5`) to blue (`1`).
+fn color(probability: f64) -> String {
+    const RED: (f64, f64, f64) = (178.0, 24.0, 43.0);
+    const BLUE: (f64, f64, f64) = (33.0, 102.0, 172.0);
+    let (target, share) = if probability < 0.5 {
+        (RED, (0.5 - probability) * 2.0)
+    } else {
+        (BLUE, (probability - 0.5) * 2.0)
+    };
+    let channel = |target: f64| (255.0 + (target - 255.0) * share).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(target.0),
+        channel(target.1),
+        channel(target.2)
+    )
+}
+
+/// Format a score the way the charts and reports label it: whole numbers without decimals, others
+/// with two.
+pub fn format_score(score: f64) -> String {
+    if score.fract() == 0.0 {
+        format!("{}", score)
+    } else {
+        format!("{:.2}", score)
+    }
+}
+
+/// Escape a value for use in SVG or HTML text content or attributes.
+pub(crate) fn escape(value: impl Display) -> String {
+    let value = value.to_string();
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn heatmap_has_a_cell_per_pair() {
+        let items = [Item("<a>"), Item("b & c")];
+        let references: Vec<_> = items.iter().collect();
+        let svg = heatmap(&references, |a, _| if a == &items[0] { 1.0 } else { 0.0 });
+
+        // The background, two diagonal cells and two probability cells.
+        assert_eq!(svg.matches("<rect").count(), 5);
+        assert!(svg.contains("fill=\"#2166ac\"><title>&lt;a&gt; beats b &amp; c: 100%</title>"));
+        assert!(svg.contains("fill=\"#b2182b\"><title>b &amp; c beats &lt;a&gt;: 0%</title>"));
+        assert!(!svg.contains("<a>"));
+    }
+
+    #[test]
+    fn score_chart_scales_to_the_largest_bound_in_the_better_direction() {
+        let items = [Item("a"), Item("b")];
+        let interval = |item, score, lower_score, upper_score| RankInterval {
+            item,
+            rank: 1,
+            best_rank: 1,
+            worst_rank: 1,
+            score,
+            lower_score,
+            upper_score,
+        };
+        let intervals = [
+            interval(&items[0], 2.0, 1.0, 4.0),
+            interval(&items[1], 0.5, 0.0, 1.0),
+        ];
+        let svg = score_chart(&intervals, Aggregation::SumOfWins);
+        assert!(svg.contains(
+            "width=\"200.0\" height=\"20.0\" fill=\"#4393c3\"><title>a: 2 (1 to 4)</title>"
+        ));
+        assert!(svg.contains("<title>b: 0.50 (0 to 1)</title>"));
+        assert_eq!(svg.matches("<path").count(), 2);
+
+        // Lower mean ranks are better, so the smallest score gets the longest bar.
+        let svg = score_chart(
+            &[
+                interval(&items[0], 1.0, 1.0, 2.0),
+                interval(&items[1], 4.0, 3.0, 4.0),
+            ],
+            Aggregation::MeanRank,
+        );
+        assert!(svg.contains("width=\"300.0\" height=\"20.0\" fill=\"#4393c3\"><title>a: 1"));
+        assert!(svg.contains("width=\"0.0\" height=\"20.0\" fill=\"#4393c3\"><title>b: 4"));
+    }
+
+    #[test]
+    fn colors_interpolate_through_white() {
+        assert_eq!(color(0.5), "#ffffff");
+        assert_eq!(color(0.0), "#b2182b");
+        assert_eq!(color(1.0), "#2166ac");
+    }
+}